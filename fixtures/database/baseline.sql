@@ -0,0 +1,65 @@
+-- database structure and data as created by the releases before the versioned migrations
+CREATE TABLE tracked_items
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    jan         INTEGER NOT NULL UNIQUE DEFAULT '0',
+    term_en     VARCHAR(255)            DEFAULT '',
+    term_jp     VARCHAR(255)            DEFAULT '',
+    description VARCHAR(255)            DEFAULT '',
+    image       VARCHAR(255)            DEFAULT '',
+    disabled    BOOLEAN                 DEFAULT FALSE NOT NULL
+);
+
+CREATE TABLE prices
+(
+    id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id            INTEGER        NOT NULL REFERENCES tracked_items (id),
+    price              DECIMAL(10, 2) NOT NULL DEFAULT '0',
+    currency           VARCHAR(255)            DEFAULT '',
+    converted_price    DECIMAL(10, 2) NOT NULL DEFAULT '0',
+    converted_currency VARCHAR(255)            DEFAULT '',
+    taxes              DECIMAL(10, 2) NOT NULL DEFAULT '0',
+    shipping           DECIMAL(10, 2) NOT NULL DEFAULT '0',
+    url                VARCHAR(255)            DEFAULT '',
+    module             VARCHAR(255)            DEFAULT '',
+    condition          VARCHAR(255)            DEFAULT '',
+    tstamp             TIMESTAMP               DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE conditions
+(
+    id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id   INTEGER        NOT NULL REFERENCES tracked_items (id),
+    type      VARCHAR(255)            DEFAULT '',
+    value     DECIMAL(10, 2) NOT NULL DEFAULT '0',
+    condition VARCHAR(255)            DEFAULT NULL,
+    disabled  BOOLEAN        NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE notifications
+(
+    id       INTEGER PRIMARY KEY AUTOINCREMENT,
+    type     VARCHAR(255) DEFAULT '',
+    tstamp   TIMESTAMP    DEFAULT CURRENT_TIMESTAMP,
+    item_id  INTEGER NOT NULL REFERENCES tracked_items (id),
+    price_id INTEGER NOT NULL REFERENCES prices (id)
+);
+
+INSERT INTO tracked_items (id, jan, term_en, term_jp, description, image, disabled)
+VALUES (1, 4580416940283, 'Saber Lily', 'セイバー・リリィ', 'Fate/Grand Order - Saber Lily', '', FALSE),
+       (2, 4560228205000, 'Rem', 'レム', 'Re:Zero - Rem', '', TRUE);
+
+INSERT INTO prices (id, item_id, price, currency, converted_price, converted_currency, taxes, shipping, url,
+                    module, condition, tstamp)
+VALUES (1, 1, 12800, 'JPY', 105.6, 'EUR', 0, 0, 'https://www.amiami.com/eng/detail/?gcode=FIGURE-035637',
+        'AmiAmi', 'new', '2020-01-12T10:00:00+00:00'),
+       (2, 1, 9980, 'JPY', 82.35, 'EUR', 0, 0, 'https://www.solarisjapan.com/products/saber-lily',
+        'SolarisJapan', 'used', '2020-01-12T10:00:05+00:00'),
+       (3, 2, 15400, 'JPY', 127.06, 'EUR', 0, 0, 'https://www.amiami.com/eng/detail/?gcode=FIGURE-012345',
+        'AmiAmi', 'new', '2020-01-12T10:00:10+00:00');
+
+INSERT INTO conditions (id, item_id, type, value, condition, disabled)
+VALUES (1, 1, 'below_price', 100, 'used', FALSE);
+
+INSERT INTO notifications (id, type, tstamp, item_id, price_id)
+VALUES (1, 'below_price', '2020-01-12T10:00:06+00:00', 1, 2);
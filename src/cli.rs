@@ -1,7 +1,13 @@
-use clap::Parser;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{ArgEnum, Parser};
 
 use crate::conditions::ConditionType;
-use crate::database::items::ItemConditions;
+use crate::currency::guesser::CurrencyGuesser;
+use crate::currency::providers::ecb::ExchangeRateFeed;
+use crate::currency::SupportedCurrency;
+use crate::database::conditions::PreOrders;
+use crate::database::items::{Grade, ItemConditions};
+use crate::output::OutputFormat;
 
 /// This application tracks wished items on multiple seller/auction sites
 /// and notifies the user about new sales/price drops and price averages
@@ -11,6 +17,9 @@ pub(crate) struct FigureTrackerOptions {
     /// Use a custom configuration file.
     #[clap(short = 'c', long = "config", default_value = "tracker.yaml")]
     pub(crate) config: String,
+    /// Use a custom database file instead of the one defined in the configuration file.
+    #[clap(long = "database", global = true)]
+    pub(crate) database: Option<String>,
     /// A level of verbosity, and can be used multiple times
     #[clap(short = 'v', long = "verbose", parse(from_occurrences), global = true)]
     pub(crate) verbose: i32,
@@ -24,6 +33,22 @@ pub(crate) enum SubCommand {
     Add(Add),
     #[clap(name = "update")]
     Update(Update),
+    #[clap(name = "list")]
+    List(List),
+    #[clap(name = "run", alias = "daemon")]
+    Run(Run),
+    #[clap(name = "history")]
+    History(History),
+    #[clap(name = "remove")]
+    Remove(Remove),
+    #[clap(name = "disable")]
+    Disable(Disable),
+    #[clap(name = "enable")]
+    Enable(Enable),
+    #[clap(name = "db")]
+    Db(Db),
+    #[clap(name = "prices")]
+    Prices(PricesCommand),
 }
 
 /// Add an item or notification condition to the database
@@ -54,6 +79,147 @@ pub(crate) enum UpdateSubCommand {
     Item(UpdateItem),
     #[clap(name = "prices")]
     Prices(UpdatePrices),
+    #[clap(name = "rates")]
+    Rates(UpdateRates),
+}
+
+/// List the tracked items, notification conditions, recorded prices, listings or sales
+#[derive(Parser, Debug)]
+pub(crate) struct List {
+    #[clap(subcommand)]
+    pub(crate) subcmd: ListSubCommand,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) enum ListSubCommand {
+    #[clap(name = "items")]
+    Items(ListItems),
+    #[clap(name = "conditions")]
+    Conditions(ListConditions),
+    #[clap(name = "prices")]
+    Prices(ListPrices),
+    #[clap(name = "listings")]
+    Listings(ListListings),
+    #[clap(name = "sales")]
+    Sales(ListSales),
+}
+
+/// Show the history of sent notifications
+#[derive(Parser, Debug)]
+pub(crate) struct History {
+    #[clap(subcommand)]
+    pub(crate) subcmd: HistorySubCommand,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) enum HistorySubCommand {
+    #[clap(name = "notifications")]
+    Notifications(HistoryNotifications),
+}
+
+/// Remove an item or notification condition from the database
+#[derive(Parser, Debug)]
+pub(crate) struct Remove {
+    #[clap(subcommand)]
+    pub(crate) subcmd: RemoveSubCommand,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) enum RemoveSubCommand {
+    #[clap(name = "item")]
+    Item(RemoveItem),
+    #[clap(name = "condition")]
+    Condition(ConditionIds),
+}
+
+/// Disable an item or notification condition without removing it
+#[derive(Parser, Debug)]
+pub(crate) struct Disable {
+    #[clap(subcommand)]
+    pub(crate) subcmd: ToggleSubCommand,
+}
+
+/// Enable a previously disabled item or notification condition again
+#[derive(Parser, Debug)]
+pub(crate) struct Enable {
+    #[clap(subcommand)]
+    pub(crate) subcmd: ToggleSubCommand,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) enum ToggleSubCommand {
+    #[clap(name = "item")]
+    Item(ItemJans),
+    #[clap(name = "condition")]
+    Condition(ConditionIds),
+}
+
+/// Keep running and check the prices of all tracked items in the configured intervals
+#[derive(Parser, Debug, Clone)]
+pub(crate) struct Run {
+    /// minutes between the price checks of an item, overrides the configured interval
+    #[clap(short = 'i', long = "interval")]
+    pub(crate) interval: Option<u64>,
+}
+
+/// Process the already recorded prices
+#[derive(Parser, Debug)]
+pub(crate) struct PricesCommand {
+    #[clap(subcommand)]
+    pub(crate) subcmd: PricesSubCommand,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) enum PricesSubCommand {
+    #[clap(name = "reconvert")]
+    Reconvert(Reconvert),
+}
+
+/// Recompute the converted prices from the original prices and currencies
+#[derive(Parser, Debug)]
+pub(crate) struct Reconvert {
+    /// currency code after ISO 4217 to convert the prices to
+    #[clap(short = 't', long = "to", parse(try_from_str = parse_currency))]
+    pub(crate) to: SupportedCurrency,
+    /// exchange rates to use, the rates of the listing date require the imported rate history
+    #[clap(arg_enum, short = 'r', long = "rate", default_value = "listing")]
+    pub(crate) rate: ReconvertRate,
+}
+
+/// the exchange rates used for the reconversion of prices
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ReconvertRate {
+    /// exchange rates which were valid on the date the price was found
+    Listing,
+    /// the latest exchange rates
+    Current,
+}
+
+/// Manage the database itself
+#[derive(Parser, Debug)]
+pub(crate) struct Db {
+    #[clap(subcommand)]
+    pub(crate) subcmd: DbSubCommand,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) enum DbSubCommand {
+    #[clap(name = "migrate")]
+    Migrate(Migrate),
+}
+
+/// Migrate the database to the latest or the passed schema version
+#[derive(Parser, Debug)]
+pub(crate) struct Migrate {
+    /// only print the applied and pending migrations without migrating the database
+    #[clap(short = 's', long = "status")]
+    pub(crate) status: bool,
+    /// schema version to migrate to, lower versions than the current one revert migrations
+    #[clap(short = 't', long = "to")]
+    pub(crate) to: Option<u32>,
+    /// output format of the status
+    #[clap(arg_enum, short = 'f', long = "format", default_value = "table")]
+    pub(crate) format: OutputFormat,
 }
 
 /// Add an item to the database
@@ -79,6 +245,8 @@ possible types are:
  - below-price-full - notification when the converted price including taxes and shipping is below <value>
  - lowest-price - notification when the price is <value> below it's lowest point since tracking the prices
  - price-drop - notification is a price is <value> percent lower than the previously detected price
+ - new-listing - notification when a new listing with a full price below <value> (0 for any price) appears
+ - back-in-stock - notification when a sold out listing with a full price below <value> (0 for any price) is available again
 "
     )]
     pub(crate) condition_type: ConditionType,
@@ -94,6 +262,43 @@ possible conditions are:
 "
     )]
     pub(crate) condition: Option<ItemConditions>,
+    #[clap(
+        arg_enum,
+        long = "min-item-grade",
+        long_help = r"option to limit notifications to listings with at least this item grade
+possible grades from best to worst are:
+ - s - unopened or mint item
+ - a - item in a very good condition
+ - b - item with minor flaws
+ - c - item with noticeable flaws
+ - junk - damaged or incomplete item
+listings without a grade only match new items
+"
+    )]
+    pub(crate) min_item_grade: Option<Grade>,
+    /// option to limit notifications to listings with at least this box grade (s, a, b, c, junk)
+    #[clap(arg_enum, long = "min-box-grade")]
+    pub(crate) min_box_grade: Option<Grade>,
+    #[clap(
+        arg_enum,
+        long = "pre-orders",
+        default_value = "include",
+        long_help = r"option to include or exclude pre-orders of not yet released items
+possible options are:
+ - include - notifies you about pre-orders and available items
+ - exclude - notifies you only about released items
+ - only - notifies you only about pre-orders
+"
+    )]
+    pub(crate) pre_orders: PreOrders,
+    /// compare only the buyout price of auctions, auctions without buyout price never match
+    /// (checked against every listing instead of the lowest prices)
+    #[clap(long = "buyout-only")]
+    pub(crate) buyout_only: bool,
+    /// notify only about auctions ending within the passed hours
+    /// (checked against every listing instead of the lowest prices)
+    #[clap(long = "ending-within")]
+    pub(crate) ending_within: Option<u32>,
     /// value of the notification condition
     #[clap(short = 'V', long = "value", required = true)]
     pub(crate) value: f64,
@@ -104,8 +309,160 @@ possible conditions are:
 
 /// Update an item manually in the database
 #[derive(Parser, Debug)]
-pub(crate) struct UpdateItem {}
+pub(crate) struct UpdateItem {
+    /// retrieve the item information again from the info modules before applying the changes
+    #[clap(short = 'u', long = "update-info")]
+    pub(crate) update_info: bool,
+    /// new description of the item
+    #[clap(short = 'd', long = "description")]
+    pub(crate) description: Option<String>,
+    /// new image URL of the item
+    #[clap(short = 'i', long = "image")]
+    pub(crate) image: Option<String>,
+    /// new english search term of the item
+    #[clap(long = "term-en")]
+    pub(crate) term_en: Option<String>,
+    /// new japanese search term of the item
+    #[clap(long = "term-jp")]
+    pub(crate) term_jp: Option<String>,
+    /// disable (true) or enable (false) the price checks of the item
+    #[clap(long = "disabled")]
+    pub(crate) disabled: Option<bool>,
+    /// JAN/EAN number of the item to update
+    #[clap(required = true)]
+    pub(crate) jan: i64,
+}
 
 /// Update prices from all registered modules
 #[derive(Parser, Debug)]
 pub(crate) struct UpdatePrices {}
+
+/// Import the exchange rates of the ECB into the exchange rate history
+#[derive(Parser, Debug)]
+pub(crate) struct UpdateRates {
+    /// feed to import, recent contains the last 90 days and full all rates since 1999
+    #[clap(arg_enum, short = 'f', long = "feed", default_value = "daily")]
+    pub(crate) feed: ExchangeRateFeed,
+}
+
+/// Remove items including their recorded prices and conditions from the database
+#[derive(Parser, Debug)]
+pub(crate) struct RemoveItem {
+    /// disable the items and their conditions instead of deleting them to keep the recorded prices
+    #[clap(short = 'a', long = "archive")]
+    pub(crate) archive: bool,
+    /// JAN/EAN numbers of the items to remove
+    #[clap(required = true, min_values = 1)]
+    pub(crate) items: Vec<i64>,
+}
+
+/// Select items by their JAN/EAN numbers
+#[derive(Parser, Debug)]
+pub(crate) struct ItemJans {
+    /// JAN/EAN numbers of the items
+    #[clap(required = true, min_values = 1)]
+    pub(crate) items: Vec<i64>,
+}
+
+/// Select notification conditions by their IDs (see list conditions)
+#[derive(Parser, Debug)]
+pub(crate) struct ConditionIds {
+    /// IDs of the notification conditions
+    #[clap(required = true, min_values = 1)]
+    pub(crate) conditions: Vec<i64>,
+}
+
+/// List all tracked items including the disabled items
+#[derive(Parser, Debug)]
+pub(crate) struct ListItems {
+    /// output format of the list
+    #[clap(arg_enum, short = 'f', long = "format", default_value = "table")]
+    pub(crate) format: OutputFormat,
+}
+
+/// List the notification conditions of all or a specific item
+#[derive(Parser, Debug)]
+pub(crate) struct ListConditions {
+    /// output format of the list
+    #[clap(arg_enum, short = 'f', long = "format", default_value = "table")]
+    pub(crate) format: OutputFormat,
+    /// JAN/EAN number of the item to limit the conditions to
+    pub(crate) jan: Option<i64>,
+}
+
+/// List the recorded prices of all or a specific item
+#[derive(Parser, Debug)]
+pub(crate) struct ListPrices {
+    /// output format of the list
+    #[clap(arg_enum, short = 'f', long = "format", default_value = "table")]
+    pub(crate) format: OutputFormat,
+    /// only list prices recorded since the passed date (YYYY-MM-DD or RFC 3339)
+    #[clap(short = 's', long = "since", parse(try_from_str = parse_since))]
+    pub(crate) since: Option<DateTime<Utc>>,
+    /// JAN/EAN number of the item to limit the prices to
+    pub(crate) jan: Option<i64>,
+}
+
+/// List all recorded listings of all or a specific item including the sold out listings
+#[derive(Parser, Debug)]
+pub(crate) struct ListListings {
+    /// output format of the list
+    #[clap(arg_enum, short = 'f', long = "format", default_value = "table")]
+    pub(crate) format: OutputFormat,
+    /// only list listings seen since the passed date (YYYY-MM-DD or RFC 3339)
+    #[clap(short = 's', long = "since", parse(try_from_str = parse_since))]
+    pub(crate) since: Option<DateTime<Utc>>,
+    /// only list sold out listings to see how fast the item sells at which price
+    #[clap(long = "sold-out")]
+    pub(crate) sold_out: bool,
+    /// JAN/EAN number of the item to limit the listings to
+    pub(crate) jan: Option<i64>,
+}
+
+/// List the recorded sales of all or a specific item with the prices the items were sold for
+#[derive(Parser, Debug)]
+pub(crate) struct ListSales {
+    /// output format of the list
+    #[clap(arg_enum, short = 'f', long = "format", default_value = "table")]
+    pub(crate) format: OutputFormat,
+    /// only list sales since the passed date (YYYY-MM-DD or RFC 3339)
+    #[clap(short = 's', long = "since", parse(try_from_str = parse_since))]
+    pub(crate) since: Option<DateTime<Utc>>,
+    /// JAN/EAN number of the item to limit the sales to
+    pub(crate) jan: Option<i64>,
+}
+
+/// List the sent notifications of all or a specific item including failed notifications
+#[derive(Parser, Debug)]
+pub(crate) struct HistoryNotifications {
+    /// output format of the list
+    #[clap(arg_enum, short = 'f', long = "format", default_value = "table")]
+    pub(crate) format: OutputFormat,
+    /// only list notifications sent since the passed date (YYYY-MM-DD or RFC 3339)
+    #[clap(short = 's', long = "since", parse(try_from_str = parse_since))]
+    pub(crate) since: Option<DateTime<Utc>>,
+    /// JAN/EAN number of the item to limit the notifications to
+    pub(crate) jan: Option<i64>,
+}
+
+/// parse the passed currency code after ISO 4217
+fn parse_currency(value: &str) -> Result<SupportedCurrency, String> {
+    CurrencyGuesser::new()
+        .guess_currency_from_code(value.to_uppercase(), true)
+        .ok_or_else(|| format!("{:?} is not a supported currency code", value))
+}
+
+/// parse the passed date or date time into a UTC timestamp
+fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date_time.with_timezone(&Utc));
+    }
+
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => Ok(DateTime::from_utc(date.and_hms(0, 0, 0), Utc)),
+        Err(_) => Err(format!(
+            "{:?} is neither a date (YYYY-MM-DD) nor a RFC 3339 timestamp",
+            value
+        )),
+    }
+}
@@ -9,6 +9,8 @@ pub(crate) enum ConditionType {
     BelowPriceFull,
     LowestPrice,
     PriceDrop,
+    NewListing,
+    BackInStock,
 }
 
 #[derive(Debug)]
@@ -30,6 +32,8 @@ impl ToString for ConditionType {
             ConditionType::BelowPriceFull => "below_price_full".to_string(),
             ConditionType::LowestPrice => "lowest_price".to_string(),
             ConditionType::PriceDrop => "price_drop".to_string(),
+            ConditionType::NewListing => "new_listing".to_string(),
+            ConditionType::BackInStock => "back_in_stock".to_string(),
         }
     }
 }
@@ -48,6 +52,8 @@ impl ConditionType {
             "below_price_full" => Ok(ConditionType::BelowPriceFull),
             "lowest_price" => Ok(ConditionType::LowestPrice),
             "price_drop" => Ok(ConditionType::PriceDrop),
+            "new_listing" => Ok(ConditionType::NewListing),
+            "back_in_stock" => Ok(ConditionType::BackInStock),
             _ => Err(InvalidConditionError {
                 msg: format!(
                     "{:?} is not a valid condition type, add --help to see the valid options",
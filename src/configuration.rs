@@ -1,177 +1,788 @@
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::error::Error;
 use std::fs::{read_to_string, File};
-use std::io;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time;
 
-use clap::Parser;
-use yaml_rust::{Yaml, YamlLoader};
+use chrono::Duration;
+use serde::{Deserialize, Deserializer};
 
-use crate::cli::FigureTrackerOptions;
-use crate::currency::guesser::CurrencyGuesser;
+use crate::currency::providers::ExchangeRateProviderType;
 use crate::currency::SupportedCurrency;
 
-pub(crate) struct Configuration {}
+/// Config is the typed representation of the configuration file
+/// it is loaded and validated once on start and passed to everything requiring it
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct Config {
+    pub(crate) database: DatabaseConfig,
+    pub(crate) general: GeneralConfig,
+    pub(crate) notifications: NotificationsConfig,
+    pub(crate) conversion: ConversionConfig,
+    pub(crate) daemon: DaemonConfig,
+    pub(crate) connection: ConnectionConfig,
+}
+
+/// settings of the used SQLite database
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct DatabaseConfig {
+    /// path to the database file, relative paths are resolved against the configuration file
+    pub(crate) path: String,
+}
+
+/// general settings of the application
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct GeneralConfig {
+    /// currency code after ISO 4217 all prices get converted to
+    pub(crate) currency: SupportedCurrency,
+}
+
+/// settings of the notification channels
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct NotificationsConfig {
+    /// hours in which the same listing with an unchanged price won't be notified again
+    pub(crate) cooldown: f64,
+    pub(crate) telegram: TelegramConfig,
+    pub(crate) discord: DiscordConfig,
+}
+
+/// settings of the telegram notifications
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct TelegramConfig {
+    pub(crate) active: bool,
+    pub(crate) api_key: Option<String>,
+    pub(crate) user_id: Option<i64>,
+}
+
+/// settings of the discord notifications
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct DiscordConfig {
+    pub(crate) active: bool,
+    pub(crate) client_token: Option<String>,
+    pub(crate) user_id: Option<i64>,
+}
+
+/// settings for the conversion of the found prices, mapped by the currency of the found prices
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct ConversionConfig {
+    /// exchange rate providers in the order of their precedence
+    pub(crate) providers: Vec<ExchangeRateProviderType>,
+    /// hours in which the cached exchange rates are used without retrieving them again
+    pub(crate) rates_ttl: f64,
+    /// static exchange rates as units of the currency equivalent to 1 EUR
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub(crate) rates: BTreeMap<SupportedCurrency, f64>,
+    /// JSON file containing exchange rates, relative paths are resolved against the configuration file
+    pub(crate) rates_file: Option<String>,
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub(crate) taxes: BTreeMap<SupportedCurrency, Option<f64>>,
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub(crate) shipping: BTreeMap<SupportedCurrency, Option<f64>>,
+    /// cost profiles mapped by the module key, overriding the shipping and taxes of the currency
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub(crate) modules: BTreeMap<String, CostProfile>,
+    /// weight classes used for the shipping costs mapped by the JAN/EAN number of the items
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub(crate) weight_classes: BTreeMap<i64, WeightClass>,
+    /// import rules mapped by the name of the destination
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub(crate) destinations: BTreeMap<String, ImportRules>,
+    /// destination the items are imported to, the flat taxes are used if not set
+    pub(crate) destination: Option<String>,
+}
+
+/// customs rules of a destination the items are imported to,
+/// all amounts are in the currency the prices get converted to
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct ImportRules {
+    /// VAT rate on the value including fees, shipping and duty
+    pub(crate) vat: f64,
+    /// customs duty rate on the value including fees and shipping
+    pub(crate) duty: f64,
+    /// fee of the courier for the customs clearance, charged if any duty or VAT is due
+    pub(crate) clearance_fee: f64,
+    /// value of the item up to which no VAT is due
+    pub(crate) vat_threshold: f64,
+    /// value of the item up to which no duty is due
+    pub(crate) duty_threshold: f64,
+}
+
+/// weight class of an item used to select the shipping costs of a cost profile
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WeightClass {
+    Small,
+    Medium,
+    Large,
+}
+
+/// costs of buying from a specific module, f.e. via a proxy service,
+/// all amounts are in the currency the prices get converted to
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct CostProfile {
+    /// fixed fee per order
+    pub(crate) fixed_fee: f64,
+    /// percentage fee on the price of the listing
+    pub(crate) service_fee: f64,
+    /// percentage surcharge of the payment on the price including fees and shipping
+    pub(crate) payment_surcharge: f64,
+    /// shipping costs by the weight class of the item
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub(crate) shipping: BTreeMap<WeightClass, f64>,
+    /// tax rate of the import, the tax rate of the currency is used if not set
+    pub(crate) taxes: Option<f64>,
+    /// destination the module ships to, f.e. the warehouse of a proxy service in the same country
+    pub(crate) destination: Option<String>,
+}
+
+/// resolved costs of a listing, all amounts are in the currency the prices get converted to
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ListingCosts {
+    pub(crate) fixed_fee: f64,
+    pub(crate) service_fee: f64,
+    pub(crate) payment_surcharge: f64,
+    pub(crate) shipping: f64,
+    pub(crate) taxes: f64,
+    /// import rules of the destination, replacing the flat taxes
+    pub(crate) import: Option<ImportRules>,
+}
+
+/// settings of the daemon mode
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct DaemonConfig {
+    /// minutes between the price checks of an item
+    pub(crate) interval: u64,
+    /// maximum random delay in seconds added to every scheduled check
+    pub(crate) jitter: u64,
+    /// custom intervals in minutes mapped by the JAN/EAN number of the items
+    #[serde(deserialize_with = "deserialize_null_default")]
+    pub(crate) items: BTreeMap<i64, u64>,
+}
+
+/// settings of the connections to the sites
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct ConnectionConfig {
+    #[serde(rename = "user-agent")]
+    pub(crate) user_agent: Option<String>,
+    pub(crate) proxy: ProxyConfig,
+}
+
+/// optional proxy used for all connections
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct ProxyConfig {
+    pub(crate) host: Option<String>,
+    pub(crate) port: Option<u16>,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+}
+
+/// custom error for invalid configuration files
+#[derive(Debug)]
+pub(crate) struct InvalidConfigurationError {
+    msg: String,
+}
+
+impl std::fmt::Display for InvalidConfigurationError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}", self.msg.clone())
+    }
+}
+
+impl Error for InvalidConfigurationError {}
+
+/// deserialize empty values (f.e. "items:") into the default value instead of failing
+fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            path: "figure_tracker.db".to_string(),
+        }
+    }
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        GeneralConfig {
+            currency: SupportedCurrency::EUR,
+        }
+    }
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            cooldown: 24.0,
+            telegram: TelegramConfig::default(),
+            discord: DiscordConfig::default(),
+        }
+    }
+}
 
-impl Configuration {
-    /// parses the passed/default configuration file or creates it if it doesn't exist yet
-    pub fn get_configuration() -> Result<Yaml, io::Error> {
-        let options = FigureTrackerOptions::parse();
-        if !Path::new(options.config.as_str()).exists() {
+impl Default for ConversionConfig {
+    fn default() -> Self {
+        ConversionConfig {
+            providers: vec![
+                ExchangeRateProviderType::Ecb,
+                ExchangeRateProviderType::Static,
+            ],
+            rates_ttl: 12.0,
+            rates: BTreeMap::new(),
+            rates_file: None,
+            taxes: BTreeMap::new(),
+            shipping: BTreeMap::new(),
+            modules: BTreeMap::new(),
+            weight_classes: BTreeMap::new(),
+            destinations: BTreeMap::new(),
+            destination: None,
+        }
+    }
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        DaemonConfig {
+            interval: 60,
+            jitter: 0,
+            items: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// parses the passed configuration file or creates it from the default configuration
+    /// if it doesn't exist yet, the parsed configuration is validated before it is returned
+    pub fn load(path: &str) -> Result<Config, Box<dyn Error>> {
+        if !Path::new(path).exists() {
             let bytes = include_bytes!("../default.yaml");
 
-            let mut file = File::create(options.config.as_str())?;
+            let mut file = File::create(path)?;
             file.write_all(bytes)?;
         }
 
-        let config_content = read_to_string(options.config.as_str())?;
-        Ok(YamlLoader::load_from_str(config_content.as_str()).unwrap()[0].clone())
+        let mut config = Config::from_str(read_to_string(path)?.as_str()).map_err(|err| {
+            Box::<dyn Error>::from(InvalidConfigurationError {
+                msg: format!("invalid configuration file {:?}: {}", path, err),
+            })
+        })?;
+
+        // relative paths in the configuration file are relative to the configuration file itself
+        config.database.path = expand_path(&config.database.path, Path::new(path).parent())
+            .to_string_lossy()
+            .to_string();
+        config.conversion.rates_file = config.conversion.rates_file.map(|rates_file| {
+            expand_path(&rates_file, Path::new(path).parent())
+                .to_string_lossy()
+                .to_string()
+        });
+
+        Ok(config)
     }
 
-    /// retrieve the used currency from the configuration file
-    /// returns EUR as the default currency if an error occurred during the retrieval
-    pub fn get_used_currency() -> SupportedCurrency {
-        if let Ok(conf) = Configuration::get_configuration() {
-            if !conf["general"]["currency"].is_badvalue() {
-                if let Some(used_currency) = CurrencyGuesser::new().guess_currency_from_code(
-                    conf["general"]["currency"].as_str().unwrap().to_string(),
-                    true,
-                ) {
-                    return used_currency;
-                }
+    /// parses and validates the passed configuration content
+    fn from_str(content: &str) -> Result<Config, Box<dyn Error>> {
+        // an empty file is a valid configuration using only the default values
+        let config: Config = if content.trim().is_empty() {
+            Config::default()
+        } else {
+            serde_yaml::from_str(content)?
+        };
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// check the values of the configuration which can't be checked by the types alone
+    fn validate(&self) -> Result<(), InvalidConfigurationError> {
+        let mut errors = vec![];
+
+        if self.database.path.is_empty() {
+            errors.push("database.path: path to the database file is not defined".to_string());
+        }
+
+        if self.notifications.cooldown < 0.0 {
+            errors.push("notifications.cooldown: cooldown can't be negative".to_string());
+        }
+
+        if self.notifications.telegram.active {
+            if self.notifications.telegram.api_key.is_none() {
+                errors.push("notifications.telegram.api_key: required if active".to_string());
+            }
+            if self.notifications.telegram.user_id.is_none() {
+                errors.push("notifications.telegram.user_id: required if active".to_string());
             }
         }
 
-        SupportedCurrency::EUR
-    }
+        if self.notifications.discord.active {
+            if self.notifications.discord.client_token.is_none() {
+                errors.push("notifications.discord.client_token: required if active".to_string());
+            }
+            if self.notifications.discord.user_id.is_none() {
+                errors.push("notifications.discord.user_id: required if active".to_string());
+            }
+        }
+
+        if self.conversion.rates_ttl < 0.0 {
+            errors.push("conversion.rates_ttl: TTL can't be negative".to_string());
+        }
 
-    /// retrieve the tax rate to the requested currency
-    pub fn get_used_tax_rate(from: SupportedCurrency) -> f64 {
-        let mut tax_rate = 0.0;
-        if let Ok(conf) = Configuration::get_configuration() {
-            if !conf["conversion"]["taxes"][from.to_string().as_str()].is_badvalue() {
-                match conf["conversion"]["taxes"][from.to_string().as_str()].clone() {
-                    Yaml::Integer(value) => return value as f64,
-                    Yaml::Real(value) => {
-                        if let Ok(value) = CurrencyGuesser::get_currency_value(value) {
-                            tax_rate = value;
-                        }
-                    }
-                    _ => {}
+        if self.conversion.providers.is_empty() {
+            errors.push("conversion.providers: at least one provider is required".to_string());
+        }
+
+        if self
+            .conversion
+            .providers
+            .contains(&ExchangeRateProviderType::Json)
+            && self.conversion.rates_file.is_none()
+        {
+            errors.push("conversion.rates_file: required for the json provider".to_string());
+        }
+
+        for (currency, rate) in self.conversion.rates.iter() {
+            if *rate <= 0.0 {
+                errors.push(format!(
+                    "conversion.rates.{}: rate has to be positive",
+                    currency
+                ));
+            }
+        }
+
+        for (key, values) in [
+            ("conversion.taxes", &self.conversion.taxes),
+            ("conversion.shipping", &self.conversion.shipping),
+        ]
+        .iter()
+        {
+            for (currency, value) in values.iter() {
+                if value.map_or(false, |value| value < 0.0) {
+                    errors.push(format!("{}.{}: value can't be negative", key, currency));
                 }
             }
         }
 
-        tax_rate
-    }
+        for (module, profile) in self.conversion.modules.iter() {
+            for (key, value) in [
+                ("fixed_fee", Some(profile.fixed_fee)),
+                ("service_fee", Some(profile.service_fee)),
+                ("payment_surcharge", Some(profile.payment_surcharge)),
+                ("taxes", profile.taxes),
+            ]
+            .iter()
+            {
+                if value.map_or(false, |value| value < 0.0) {
+                    errors.push(format!(
+                        "conversion.modules.{}.{}: value can't be negative",
+                        module, key
+                    ));
+                }
+            }
 
-    /// retrieve the shipping costs based on the passed currency
-    pub fn get_shipping(from: SupportedCurrency) -> f64 {
-        let mut shipping = 0.0;
-        if let Ok(conf) = Configuration::get_configuration() {
-            if !conf["conversion"]["shipping"][from.to_string().as_str()].is_badvalue() {
-                match conf["conversion"]["shipping"][from.to_string().as_str()].clone() {
-                    Yaml::Integer(value) => return value as f64,
-                    Yaml::Real(value) => {
-                        if let Ok(value) = CurrencyGuesser::get_currency_value(value) {
-                            shipping = value;
-                        }
-                    }
-                    _ => {}
+            if let Some(destination) = &profile.destination {
+                if !self.conversion.destinations.contains_key(destination) {
+                    errors.push(format!(
+                        "conversion.modules.{}.destination: unknown destination {:?}",
+                        module, destination
+                    ));
                 }
             }
-        }
 
-        shipping
-    }
-
-    /// retrieve the telegram API key is the telegram notification is active and the api key is set
-    pub fn get_telegram_api_key() -> Option<String> {
-        if let Ok(conf) = Configuration::get_configuration() {
-            if !conf["notifications"]["telegram"]["active"].is_badvalue() {
-                if let Some(active) = conf["notifications"]["telegram"]["active"].as_bool() {
-                    if active
-                        && !conf["notifications"]["telegram"]["api_key"].is_badvalue()
-                        && conf["notifications"]["telegram"]["api_key"]
-                            .as_str()
-                            .is_some()
-                    {
-                        return Some(
-                            conf["notifications"]["telegram"]["api_key"]
-                                .as_str()
-                                .unwrap()
-                                .to_string(),
-                        );
-                    }
+            for (weight_class, value) in profile.shipping.iter() {
+                if *value < 0.0 {
+                    errors.push(format!(
+                        "conversion.modules.{}.shipping.{:?}: value can't be negative",
+                        module, weight_class
+                    ));
                 }
             }
         }
 
-        None
-    }
+        if let Some(destination) = &self.conversion.destination {
+            if !self.conversion.destinations.contains_key(destination) {
+                errors.push(format!(
+                    "conversion.destination: unknown destination {:?}",
+                    destination
+                ));
+            }
+        }
 
-    /// retrieve the user ID of the telegram notification option is telegram notification is active
-    /// and the user ID is set
-    pub fn get_telegram_user_id() -> Option<i64> {
-        if let Ok(conf) = Configuration::get_configuration() {
-            if !conf["notifications"]["telegram"]["active"].is_badvalue() {
-                if let Some(active) = conf["notifications"]["telegram"]["active"].as_bool() {
-                    if active
-                        && !conf["notifications"]["telegram"]["user_id"].is_badvalue()
-                        && conf["notifications"]["telegram"]["user_id"]
-                            .as_i64()
-                            .is_some()
-                    {
-                        return conf["notifications"]["telegram"]["user_id"].as_i64();
-                    }
+        for (destination, rules) in self.conversion.destinations.iter() {
+            for (key, value) in [
+                ("vat", rules.vat),
+                ("duty", rules.duty),
+                ("clearance_fee", rules.clearance_fee),
+                ("vat_threshold", rules.vat_threshold),
+                ("duty_threshold", rules.duty_threshold),
+            ]
+            .iter()
+            {
+                if *value < 0.0 {
+                    errors.push(format!(
+                        "conversion.destinations.{}.{}: value can't be negative",
+                        destination, key
+                    ));
                 }
             }
         }
 
-        None
-    }
-
-    /// retrieve the discord bot token if discord notifications are active and the token is set
-    pub fn get_discord_client_token() -> Option<String> {
-        if let Ok(conf) = Configuration::get_configuration() {
-            if !conf["notifications"]["discord"]["active"].is_badvalue() {
-                if let Some(active) = conf["notifications"]["discord"]["active"].as_bool() {
-                    if active
-                        && !conf["notifications"]["discord"]["client_token"].is_badvalue()
-                        && conf["notifications"]["discord"]["client_token"]
-                            .as_str()
-                            .is_some()
-                    {
-                        return Some(
-                            conf["notifications"]["discord"]["client_token"]
-                                .as_str()
-                                .unwrap()
-                                .to_string(),
-                        );
-                    }
-                }
+        if self.daemon.interval == 0 {
+            errors.push("daemon.interval: interval has to be at least 1 minute".to_string());
+        }
+
+        for (jan, interval) in self.daemon.items.iter() {
+            if *interval == 0 {
+                errors.push(format!(
+                    "daemon.items.{}: interval has to be at least 1 minute",
+                    jan
+                ));
             }
         }
 
-        None
+        if self.connection.proxy.username.is_some() != self.connection.proxy.password.is_some() {
+            errors.push(
+                "connection.proxy: username and password have to be set together".to_string(),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidConfigurationError {
+                msg: errors.join(", "),
+            })
+        }
     }
+}
 
-    /// retrieve the user ID if discord notifications are active and the user ID is set
-    pub fn get_discord_user_id() -> Option<i64> {
-        if let Ok(conf) = Configuration::get_configuration() {
-            if !conf["notifications"]["discord"]["active"].is_badvalue() {
-                if let Some(active) = conf["notifications"]["discord"]["active"].as_bool() {
-                    if active
-                        && !conf["notifications"]["discord"]["user_id"].is_badvalue()
-                        && conf["notifications"]["discord"]["user_id"]
-                            .as_i64()
-                            .is_some()
-                    {
-                        return conf["notifications"]["discord"]["user_id"].as_i64();
-                    }
-                }
+/// expands a leading "~" to the home directory of the user and resolves relative paths
+/// against the passed base directory, paths without a base directory are left relative
+pub(crate) fn expand_path(path: &str, base: Option<&Path>) -> PathBuf {
+    let expanded = if path == "~" || path.starts_with("~/") {
+        match env::var_os("HOME") {
+            Some(home) => {
+                PathBuf::from(home).join(path.trim_start_matches('~').trim_start_matches('/'))
+            }
+            None => PathBuf::from(path),
+        }
+    } else {
+        PathBuf::from(path)
+    };
+
+    match base {
+        Some(base) if expanded.is_relative() => base.join(expanded),
+        _ => expanded,
+    }
+}
+
+impl NotificationsConfig {
+    /// retrieve the cooldown in which the same listing with an unchanged price won't be notified again
+    pub fn get_cooldown(&self) -> Duration {
+        Duration::seconds((self.cooldown * 3600.0) as i64)
+    }
+}
+
+impl ConversionConfig {
+    /// retrieve the duration in which the cached exchange rates are used without retrieving them again
+    pub fn get_rates_ttl(&self) -> Duration {
+        Duration::seconds((self.rates_ttl * 3600.0) as i64)
+    }
+
+    /// retrieve the tax rate of prices in the passed currency
+    pub fn get_tax_rate(&self, from: &SupportedCurrency) -> f64 {
+        self.taxes.get(from).cloned().flatten().unwrap_or(0.0)
+    }
+
+    /// retrieve the shipping costs of prices in the passed currency
+    pub fn get_shipping(&self, from: &SupportedCurrency) -> f64 {
+        self.shipping.get(from).cloned().flatten().unwrap_or(0.0)
+    }
+
+    /// retrieve the weight class of the passed item, medium if not configured
+    pub fn get_weight_class(&self, jan: i64) -> WeightClass {
+        self.weight_classes
+            .get(&jan)
+            .cloned()
+            .unwrap_or(WeightClass::Medium)
+    }
+
+    /// retrieve the costs of a listing of the passed item found by the passed module,
+    /// the shipping costs and tax rate of the currency are used if the module doesn't define them
+    /// and the import rules of the destination are used instead of the tax rate if configured
+    pub fn get_listing_costs(
+        &self,
+        module: &str,
+        from: &SupportedCurrency,
+        jan: i64,
+    ) -> ListingCosts {
+        let mut costs = ListingCosts {
+            shipping: self.get_shipping(from),
+            taxes: self.get_tax_rate(from),
+            ..ListingCosts::default()
+        };
+
+        let mut destination = self.destination.as_ref();
+        if let Some(profile) = self.modules.get(module) {
+            costs.fixed_fee = profile.fixed_fee;
+            costs.service_fee = profile.service_fee;
+            costs.payment_surcharge = profile.payment_surcharge;
+            if let Some(shipping) = profile.shipping.get(&self.get_weight_class(jan)) {
+                costs.shipping = *shipping;
+            }
+            if let Some(taxes) = profile.taxes {
+                costs.taxes = taxes;
+            }
+            if profile.destination.is_some() {
+                destination = profile.destination.as_ref();
             }
         }
 
-        None
+        // the import rules of the destination replace the flat taxes
+        if let Some(rules) = destination.and_then(|destination| self.destinations.get(destination))
+        {
+            costs.taxes = rules.vat;
+            costs.import = Some(rules.clone());
+        }
+
+        costs
+    }
+}
+
+impl DaemonConfig {
+    /// retrieve the interval between the price checks of an item
+    pub fn get_interval(&self) -> time::Duration {
+        time::Duration::from_secs(self.interval * 60)
+    }
+
+    /// retrieve the maximum random delay added to every scheduled check
+    pub fn get_jitter(&self) -> time::Duration {
+        time::Duration::from_secs(self.jitter)
+    }
+
+    /// retrieve the custom check intervals mapped by the JAN/EAN number of the items
+    pub fn get_item_intervals(&self) -> HashMap<i64, time::Duration> {
+        self.items
+            .iter()
+            .map(|(jan, minutes)| (*jan, time::Duration::from_secs(minutes * 60)))
+            .collect()
+    }
+}
+
+#[test]
+fn test_default_configuration() {
+    let config = Config::from_str(include_str!("../default.yaml")).unwrap();
+
+    assert_eq!(config.database.path, "figure_tracker.db");
+    assert_eq!(config.general.currency, SupportedCurrency::EUR);
+    assert_eq!(config.conversion.get_tax_rate(&SupportedCurrency::JPY), 0.0);
+    assert_eq!(
+        config.daemon.get_interval(),
+        time::Duration::from_secs(3600)
+    );
+    assert!(config.daemon.items.is_empty());
+    assert!(!config.notifications.telegram.active);
+    assert_eq!(
+        config.conversion.providers,
+        vec![
+            ExchangeRateProviderType::Ecb,
+            ExchangeRateProviderType::Static
+        ]
+    );
+    assert!(config.conversion.rates.is_empty());
+}
+
+#[test]
+fn test_configuration_values() {
+    let config = Config::from_str(
+        "general:
+  currency: USD
+conversion:
+  taxes:
+    JPY: 0.19
+  shipping:
+    JPY: 40
+daemon:
+  items:
+    4580416940283: 15",
+    )
+    .unwrap();
+
+    assert_eq!(config.general.currency, SupportedCurrency::USD);
+    assert_eq!(
+        config.conversion.get_tax_rate(&SupportedCurrency::JPY),
+        0.19
+    );
+    assert_eq!(
+        config.conversion.get_shipping(&SupportedCurrency::JPY),
+        40.0
+    );
+    assert_eq!(config.conversion.get_shipping(&SupportedCurrency::USD), 0.0);
+    assert_eq!(
+        config.daemon.get_item_intervals().get(&4_580_416_940_283),
+        Some(&time::Duration::from_secs(900))
+    );
+}
+
+#[test]
+fn test_listing_costs() {
+    let config = Config::from_str(
+        "conversion:
+  taxes:
+    JPY: 0.19
+  shipping:
+    JPY: 40
+  modules:
+    amazon.co.jp:
+      fixed_fee: 5
+      service_fee: 0.05
+      payment_surcharge: 0.03
+      shipping:
+        small: 15
+        large: 60
+  weight_classes:
+    4580416940283: large",
+    )
+    .unwrap();
+
+    // modules without cost profile use the costs of the currency
+    assert_eq!(
+        config.conversion.get_listing_costs(
+            "amiami.com",
+            &SupportedCurrency::JPY,
+            4_580_416_940_283
+        ),
+        ListingCosts {
+            shipping: 40.0,
+            taxes: 0.19,
+            ..ListingCosts::default()
+        }
+    );
+    assert_eq!(
+        config.conversion.get_listing_costs(
+            "amazon.co.jp",
+            &SupportedCurrency::JPY,
+            4_580_416_940_283
+        ),
+        ListingCosts {
+            fixed_fee: 5.0,
+            service_fee: 0.05,
+            payment_surcharge: 0.03,
+            shipping: 60.0,
+            taxes: 0.19,
+            import: None,
+        }
+    );
+    // items without weight class are medium, which has no shipping costs in the profile
+    assert_eq!(
+        config
+            .conversion
+            .get_listing_costs("amazon.co.jp", &SupportedCurrency::JPY, 1)
+            .shipping,
+        40.0
+    );
+}
+
+#[test]
+fn test_import_rules() {
+    let config = Config::from_str(
+        "conversion:
+  taxes:
+    JPY: 0.19
+  destination: eu
+  destinations:
+    eu:
+      vat: 0.19
+      duty: 0.047
+      clearance_fee: 6
+      duty_threshold: 150
+    us-warehouse:
+      vat: 0.0
+  modules:
+    rakuten.co.jp:
+      destination: us-warehouse",
+    )
+    .unwrap();
+
+    let costs = config
+        .conversion
+        .get_listing_costs("amiami.com", &SupportedCurrency::JPY, 1);
+    assert_eq!(costs.taxes, 0.19);
+    assert_eq!(costs.import.unwrap().duty, 0.047);
+
+    // the destination of the cost profile overrides the global destination
+    let costs = config
+        .conversion
+        .get_listing_costs("rakuten.co.jp", &SupportedCurrency::JPY, 1);
+    assert_eq!(costs.taxes, 0.0);
+    assert_eq!(costs.import.unwrap().duty, 0.0);
+
+    // unknown destinations
+    assert!(Config::from_str("conversion:\n  destination: eu").is_err());
+}
+
+#[test]
+fn test_invalid_configuration() {
+    // unknown keys
+    assert!(Config::from_str("general:\n  currenzy: EUR").is_err());
+    // unknown currencies
+    assert!(Config::from_str("general:\n  currency: EURO").is_err());
+    // invalid types
+    assert!(Config::from_str("daemon:\n  interval: hourly").is_err());
+    // missing values of active notification channels
+    assert!(Config::from_str("notifications:\n  telegram:\n    active: true").is_err());
+    // json provider without a rates file and non-positive static rates
+    assert!(Config::from_str("conversion:\n  providers: [ecb, json]").is_err());
+    assert!(Config::from_str("conversion:\n  rates:\n    TWD: 0").is_err());
+    // negative fees and unknown weight classes of the cost profiles
+    assert!(
+        Config::from_str("conversion:\n  modules:\n    amiami.com:\n      fixed_fee: -5").is_err()
+    );
+    assert!(Config::from_str(
+        "conversion:\n  modules:\n    amiami.com:\n      shipping:\n        huge: 80"
+    )
+    .is_err());
+}
+
+#[test]
+fn test_expand_path() {
+    let base = Path::new("/etc/figure-tracker");
+
+    assert_eq!(
+        expand_path("figure_tracker.db", Some(base)),
+        PathBuf::from("/etc/figure-tracker/figure_tracker.db")
+    );
+    assert_eq!(
+        expand_path("/var/lib/tracker.db", Some(base)),
+        PathBuf::from("/var/lib/tracker.db")
+    );
+    assert_eq!(
+        expand_path("data/tracker.db", None),
+        PathBuf::from("data/tracker.db")
+    );
+
+    if let Some(home) = env::var_os("HOME") {
+        assert_eq!(
+            expand_path("~/tracker/alice.db", Some(base)),
+            PathBuf::from(home).join("tracker/alice.db")
+        );
     }
 }
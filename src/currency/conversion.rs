@@ -1,90 +1,170 @@
 use std::collections::BTreeMap;
 use std::error::Error;
 
-use serde::Deserialize;
-
+use crate::configuration::ConversionConfig;
 use crate::currency::guesser::CurrencyGuesser;
+use crate::currency::money::Money;
+use crate::currency::providers::config::ConfigRates;
+use crate::currency::providers::ecb::Ecb;
+use crate::currency::providers::json::JsonFile;
+use crate::currency::providers::{ExchangeRateProvider, ExchangeRateProviderType};
 use crate::currency::SupportedCurrency;
-use crate::http::get_client;
+use crate::database::exchange_rates::ExchangeRate;
+use crate::database::Database;
+use crate::http::Client;
 
-/// structs used for the deserialization of the EUR reference values of the ECB
-#[derive(Deserialize)]
-struct Envelope {
-    #[serde(rename = "Cube")]
-    pub cube: Cube,
+/// struct to use most current exchange rates to convert currencies to one equal currency
+#[derive(Clone)]
+pub(crate) struct CurrencyConversion {
+    pub(crate) exchange_rates: BTreeMap<SupportedCurrency, f64>,
 }
 
-#[derive(Deserialize)]
-struct Cube {
-    #[serde(rename = "Cube")]
-    pub time: CubeTime,
+/// custom error if no exchange rate is known for a currency
+#[derive(Debug)]
+pub(crate) struct MissingExchangeRateError {
+    currency: SupportedCurrency,
 }
 
-#[derive(Deserialize)]
-struct CubeTime {
-    #[serde(rename = "time")]
-    pub date: String,
-    #[serde(rename = "Cube")]
-    pub currencies: Vec<CubeCurrency>,
+impl std::fmt::Display for MissingExchangeRateError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "no exchange rate known for currency: {}",
+            self.currency
+        )
+    }
 }
 
-#[derive(Deserialize)]
-struct CubeCurrency {
-    pub currency: String,
-    pub rate: String,
+impl Error for MissingExchangeRateError {}
+
+/// custom error if none of the configured providers returned any exchange rates
+#[derive(Debug)]
+pub(crate) struct NoExchangeRatesError {
+    msg: String,
 }
 
-/// struct to use most current exchange rates to convert currencies to one equal currency
-#[derive(Clone)]
-pub(crate) struct CurrencyConversion {
-    pub(crate) exchange_rates: BTreeMap<SupportedCurrency, f64>,
+impl std::fmt::Display for NoExchangeRatesError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "no exchange rates available: {}", self.msg)
+    }
 }
 
+impl Error for NoExchangeRatesError {}
+
 ///implementation for the currency conversion
 impl CurrencyConversion {
-    /// retrieve instance of currency conversion with initialized currencies and exchange rates
-    pub fn new() -> Result<Self, Box<dyn Error>> {
-        Ok(CurrencyConversion {
-            exchange_rates: CurrencyConversion::get_conversion_rates()?,
-        })
+    /// retrieve instance of currency conversion with the exchange rates of the configured providers
+    pub fn new(
+        config: &ConversionConfig,
+        client: &Client,
+        db: &Database,
+    ) -> Result<Self, Box<dyn Error>> {
+        let providers: Vec<Box<dyn ExchangeRateProvider + '_>> = config
+            .providers
+            .iter()
+            .map(|provider| -> Box<dyn ExchangeRateProvider + '_> {
+                match provider {
+                    ExchangeRateProviderType::Ecb => {
+                        Box::new(Ecb::new(client, db, config.get_rates_ttl()))
+                    }
+                    ExchangeRateProviderType::Static => Box::new(ConfigRates::new(&config.rates)),
+                    // the configuration validation ensures that the file is set for this provider
+                    ExchangeRateProviderType::Json => Box::new(JsonFile::new(
+                        config.rates_file.clone().unwrap_or_default().into(),
+                    )),
+                }
+            })
+            .collect();
+
+        CurrencyConversion::from_providers(&providers)
     }
 
-    /// update the conversion rates using the ECB euro reference
-    fn get_conversion_rates() -> Result<BTreeMap<SupportedCurrency, f64>, Box<dyn Error>> {
+    /// create the currency conversion from the passed chain of providers,
+    /// rates of earlier providers take precedence over the rates of later providers
+    /// and failing providers are skipped as long as any provider returned exchange rates
+    pub fn from_providers(
+        providers: &[Box<dyn ExchangeRateProvider + '_>],
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut exchange_rates: Vec<ExchangeRate> = vec![];
+        let mut errors = vec![];
+
+        for provider in providers.iter() {
+            match provider.get_exchange_rates() {
+                Ok(rates) => {
+                    debug!(
+                        "retrieved {} exchange rates from provider: {}",
+                        rates.len(),
+                        provider.get_provider_key()
+                    );
+                    for rate in rates.into_iter() {
+                        if !exchange_rates
+                            .iter()
+                            .any(|known_rate| known_rate.currency == rate.currency)
+                        {
+                            exchange_rates.push(rate);
+                        }
+                    }
+                }
+                Err(err) => {
+                    warn!(
+                        "unable to retrieve exchange rates from provider: {} (err: {:?})",
+                        provider.get_provider_key(),
+                        err
+                    );
+                    errors.push(format!("{}: {}", provider.get_provider_key(), err));
+                }
+            }
+        }
+
+        if exchange_rates.is_empty() {
+            return Err(Box::new(NoExchangeRatesError {
+                msg: if errors.is_empty() {
+                    "no exchange rate providers configured".to_string()
+                } else {
+                    errors.join(", ")
+                },
+            }));
+        }
+
+        Ok(CurrencyConversion::from_rates(&exchange_rates))
+    }
+
+    /// create the currency conversion from the passed EUR reference rates
+    pub fn from_rates(rates: &[ExchangeRate]) -> Self {
         let mut exchange_rates: BTreeMap<SupportedCurrency, f64> = BTreeMap::new();
         // insert the base currency here which is not in the exchange information
         exchange_rates.insert(SupportedCurrency::EUR, 1.00);
 
-        let res = get_client()?
-            .get("https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml")
-            .send()?;
-        let envelope: Envelope = quick_xml::de::from_str(&res.text()?).unwrap();
         let currency_guesser = CurrencyGuesser::new();
-
-        info!(
-            "extracted exchange rates from date: {:?}",
-            envelope.cube.time.date
-        );
-
-        for currency in envelope.cube.time.currencies.iter() {
-            let exchange_rate: f64 = currency.rate.parse()?;
-            if let Some(guessed_currency) = currency_guesser
-                .guess_currency_from_code(currency.currency.as_str().to_string(), true)
+        for rate in rates.iter() {
+            if let Some(currency) =
+                currency_guesser.guess_currency_from_code(rate.currency.clone(), true)
             {
-                exchange_rates.insert(guessed_currency.clone(), exchange_rate);
+                exchange_rates.insert(currency, rate.rate);
             }
         }
 
-        Ok(exchange_rates)
+        CurrencyConversion { exchange_rates }
+    }
+
+    /// retrieve the exchange rate of the passed currency
+    fn get_exchange_rate(&self, currency: &SupportedCurrency) -> Result<f64, Box<dyn Error>> {
+        match self.exchange_rates.get(currency) {
+            Some(rate) => Ok(*rate),
+            None => Err(Box::new(MissingExchangeRateError {
+                currency: currency.clone(),
+            })),
+        }
     }
 
-    /// convert the passed value to the equivalent of the passed to currency
+    /// convert the passed value to the equivalent of the passed to currency,
+    /// the converted value is rounded to the minor unit of the to currency
     pub fn convert_price_to(
         &self,
-        value: f64,
-        from: SupportedCurrency,
+        value: &Money,
         to: SupportedCurrency,
-    ) -> f64 {
-        value / *self.exchange_rates.get(&from).unwrap() * *self.exchange_rates.get(&to).unwrap()
+    ) -> Result<Money, Box<dyn Error>> {
+        let rate = self.get_exchange_rate(&to)? / self.get_exchange_rate(&value.currency)?;
+        Ok(Money::from_major(value.to_major() * rate, to))
     }
 }
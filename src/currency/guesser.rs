@@ -5,6 +5,7 @@ use num_traits::ToPrimitive;
 use ordered_float::OrderedFloat;
 use strsim::normalized_levenshtein;
 
+use crate::currency::money::Money;
 use crate::currency::SupportedCurrency;
 
 /// struct to store our supported currencies retrieved from the ECB
@@ -67,6 +68,11 @@ impl CurrencyGuesser {
                 (SupportedCurrency::SGD, vec!["$".to_string()]),
                 (SupportedCurrency::THB, vec!["฿".to_string()]),
                 (SupportedCurrency::ZAR, vec!["R".to_string()]),
+                // not published by the ECB, requires a static or JSON file exchange rate
+                (
+                    SupportedCurrency::TWD,
+                    vec!["$".to_string(), "NT$".to_string()],
+                ),
             ]
             .iter()
             .cloned()
@@ -131,9 +137,22 @@ impl CurrencyGuesser {
         None
     }
 
-    /// retrieve the numerical value of the passed amount
-    pub fn get_currency_value(value: String) -> Result<f64, Box<dyn Error>> {
-        let currency = currency::Currency::from_str(value.as_str())?;
-        Ok(currency.value().to_f64().unwrap() / 100.0)
+    /// retrieve the exact value of the passed amount in the passed currency,
+    /// values with more decimal places than the currency has are rounded
+    pub fn get_currency_value(
+        value: String,
+        currency: SupportedCurrency,
+    ) -> Result<Money, Box<dyn Error>> {
+        // the parsed value is always in hundredths of the major unit
+        let hundredths = currency::Currency::from_str(value.as_str())?
+            .value()
+            .to_i64()
+            .ok_or_else(|| format!("value {:?} is out of range", value))?;
+        let minor_units = hundredths * currency.get_minor_factor();
+
+        Ok(Money::new(
+            (minor_units + 50 * minor_units.signum()) / 100,
+            currency,
+        ))
     }
 }
@@ -1,11 +1,20 @@
 use std::fmt;
 use std::fmt::Display;
 
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::ToSql;
+use serde::Deserialize;
+
+use crate::currency::guesser::CurrencyGuesser;
+
 pub(crate) mod conversion;
 pub(crate) mod guesser;
+pub(crate) mod money;
+pub(crate) mod providers;
 
-/// the supported currencies from the ECB
-#[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord)]
+/// the supported currencies, all currencies of the ECB and additional currencies
+/// which require exchange rates from other providers
+#[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord, Deserialize)]
 pub(crate) enum SupportedCurrency {
     EUR,
     USD,
@@ -40,6 +49,7 @@ pub(crate) enum SupportedCurrency {
     SGD,
     THB,
     ZAR,
+    TWD,
 }
 
 /// implement to_string functionality for SupportedCurrency
@@ -49,25 +59,231 @@ impl Display for SupportedCurrency {
     }
 }
 
+impl SupportedCurrency {
+    /// retrieve the number of decimal places of the minor unit after ISO 4217
+    pub fn get_exponent(&self) -> u32 {
+        match self {
+            SupportedCurrency::JPY | SupportedCurrency::KRW | SupportedCurrency::ISK => 0,
+            _ => 2,
+        }
+    }
+
+    /// retrieve the amount of minor units in one major unit of the currency
+    pub fn get_minor_factor(&self) -> i64 {
+        10_i64.pow(self.get_exponent())
+    }
+}
+
+/// implementation for the ToSql trait for the rusqlite dependency
+/// to store the currency code
+impl ToSql for SupportedCurrency {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+/// implementation for the FromSql trait for the rusqlite dependency
+/// to only allow supported currency codes
+impl FromSql for SupportedCurrency {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str().and_then(|s| {
+            CurrencyGuesser::new()
+                .guess_currency_from_code(s.to_string(), true)
+                .ok_or(FromSqlError::InvalidType)
+        })
+    }
+}
+
 #[test]
 fn test_currency_conversion() {
+    use crate::configuration::ConversionConfig;
     use crate::currency::conversion::CurrencyConversion;
+    use crate::currency::money::Money;
+    use crate::database::Database;
+    use crate::http::Client;
+
+    let db = Database::open(":memory:").unwrap();
+    let config = ConversionConfig::default();
 
-    match CurrencyConversion::new() {
+    match CurrencyConversion::new(&config, &Client::from_fixture("ecb/daily"), &db) {
         Ok(converter) => {
-            println!(
-                "$150 are currently {}€",
-                converter.convert_price_to(150.0, SupportedCurrency::USD, SupportedCurrency::EUR)
+            assert_eq!(
+                converter
+                    .convert_price_to(
+                        &Money::new(15000, SupportedCurrency::USD),
+                        SupportedCurrency::EUR
+                    )
+                    .unwrap(),
+                Money::new(13843, SupportedCurrency::EUR)
+            );
+            assert_eq!(
+                converter
+                    .convert_price_to(
+                        &Money::new(15000, SupportedCurrency::EUR),
+                        SupportedCurrency::USD
+                    )
+                    .unwrap()
+                    .to_string(),
+                "162.54 USD"
             );
-            println!(
-                "150€ are currently ${}",
-                converter.convert_price_to(150.0, SupportedCurrency::EUR, SupportedCurrency::USD)
+            // currencies without minor unit are rounded to full units
+            assert_eq!(
+                converter
+                    .convert_price_to(
+                        &Money::new(10000, SupportedCurrency::EUR),
+                        SupportedCurrency::JPY
+                    )
+                    .unwrap(),
+                Money::new(11907, SupportedCurrency::JPY)
             );
+            // the ECB doesn't publish exchange rates for the taiwan dollar
+            assert!(converter
+                .convert_price_to(
+                    &Money::new(15000, SupportedCurrency::TWD),
+                    SupportedCurrency::EUR
+                )
+                .is_err());
         }
         Err(err) => panic!("{:?}", err.to_string()),
     }
 }
 
+#[test]
+fn test_cached_currency_conversion() {
+    use crate::configuration::ConversionConfig;
+    use crate::currency::conversion::CurrencyConversion;
+    use crate::database::Database;
+    use crate::http::Client;
+
+    let db = Database::open(":memory:").unwrap();
+    let unavailable = Client::from_fixture("ecb/unavailable");
+    let mut config = ConversionConfig::default();
+
+    // no exchange rates are known yet, so the ECB has to be reachable
+    assert!(CurrencyConversion::new(&config, &unavailable, &db).is_err());
+
+    assert!(CurrencyConversion::new(&config, &Client::from_fixture("ecb/daily"), &db).is_ok());
+
+    // the cached exchange rates are used within the TTL and as fallback after the TTL
+    for ttl in vec![12.0, 0.0] {
+        config.rates_ttl = ttl;
+        let converter = CurrencyConversion::new(&config, &unavailable, &db).unwrap();
+        assert_eq!(
+            converter.exchange_rates.get(&SupportedCurrency::JPY),
+            Some(&119.07)
+        );
+        assert_eq!(
+            converter.exchange_rates.get(&SupportedCurrency::EUR),
+            Some(&1.0)
+        );
+    }
+}
+
+#[test]
+fn test_exchange_rate_providers() {
+    use crate::configuration::ConversionConfig;
+    use crate::currency::conversion::CurrencyConversion;
+    use crate::currency::money::Money;
+    use crate::currency::providers::ExchangeRateProviderType;
+    use crate::database::Database;
+    use crate::http::Client;
+
+    let mut config = ConversionConfig::default();
+    config.providers = vec![
+        ExchangeRateProviderType::Ecb,
+        ExchangeRateProviderType::Static,
+        ExchangeRateProviderType::Json,
+    ];
+    config.rates = vec![(SupportedCurrency::TWD, 33.1)].into_iter().collect();
+    config.rates_file = Some(format!(
+        "{}/fixtures/rates/rates.json",
+        env!("CARGO_MANIFEST_DIR")
+    ));
+
+    // earlier providers take precedence over the later providers
+    let converter = CurrencyConversion::new(
+        &config,
+        &Client::from_fixture("ecb/daily"),
+        &Database::open(":memory:").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        converter.exchange_rates.get(&SupportedCurrency::JPY),
+        Some(&119.07)
+    );
+    assert_eq!(
+        converter
+            .convert_price_to(
+                &Money::new(33100, SupportedCurrency::TWD),
+                SupportedCurrency::EUR
+            )
+            .unwrap(),
+        Money::new(1000, SupportedCurrency::EUR)
+    );
+
+    // unavailable providers are skipped
+    let converter = CurrencyConversion::new(
+        &config,
+        &Client::from_fixture("ecb/unavailable"),
+        &Database::open(":memory:").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        converter.exchange_rates.get(&SupportedCurrency::JPY),
+        Some(&100.0)
+    );
+    assert_eq!(
+        converter.exchange_rates.get(&SupportedCurrency::TWD),
+        Some(&33.1)
+    );
+    assert!(converter
+        .convert_price_to(
+            &Money::new(10000, SupportedCurrency::USD),
+            SupportedCurrency::EUR
+        )
+        .is_err());
+}
+
+#[test]
+fn test_exchange_rate_history() {
+    use crate::currency::conversion::CurrencyConversion;
+    use crate::currency::money::Money;
+    use crate::currency::providers::ecb::{Ecb, ExchangeRateFeed};
+    use crate::http::Client;
+
+    let rates = Ecb::get_feed_rates(
+        &Client::from_fixture("ecb/history"),
+        ExchangeRateFeed::Recent,
+    )
+    .unwrap();
+
+    // 3 days with 3 supported currencies each, the obsolete cypriot pound is skipped
+    assert_eq!(rates.len(), 9);
+    assert!(rates.iter().all(|rate| rate.currency != "CYP"));
+
+    let converter = CurrencyConversion::from_rates(
+        &rates
+            .into_iter()
+            .filter(|rate| rate.reference_date.to_string() == "2020-02-12")
+            .collect::<Vec<_>>(),
+    );
+    assert!(converter
+        .convert_price_to(
+            &Money::new(100, SupportedCurrency::JPY),
+            SupportedCurrency::CAD
+        )
+        .is_err());
+    assert_eq!(
+        converter
+            .convert_price_to(
+                &Money::new(12045, SupportedCurrency::JPY),
+                SupportedCurrency::EUR
+            )
+            .unwrap(),
+        Money::new(10000, SupportedCurrency::EUR)
+    );
+}
+
 #[test]
 fn test_currency_guesses() {
     use crate::currency::guesser::CurrencyGuesser;
@@ -83,6 +299,7 @@ fn test_currency_guesses() {
         ("£420.00", Some(SupportedCurrency::GBP)),
         ("A$180.00", Some(SupportedCurrency::AUD)),
         ("HK$520.00", Some(SupportedCurrency::HKD)),
+        ("NT$1,200", Some(SupportedCurrency::TWD)),
     ];
 
     for (test_value, expected_currency) in test_values {
@@ -101,34 +318,62 @@ fn test_currency_guesses() {
 #[test]
 pub fn test_currency_value_guesses() {
     use crate::currency::guesser::CurrencyGuesser;
+    use crate::currency::money::Money;
 
-    let test_values: Vec<(&str, f64)> = vec![
+    let test_values: Vec<(&str, SupportedCurrency, i64)> = vec![
         // no decimals, no separators
-        ("123", 123.0),
+        ("123", SupportedCurrency::EUR, 12_300),
         // with decimals, no separators
-        ("123.00", 123.0),
-        ("123,00", 123.0),
-        ("123456.00", 123_456.0),
-        ("123456,00", 123_456.0),
+        ("123.00", SupportedCurrency::EUR, 12_300),
+        ("123,00", SupportedCurrency::EUR, 12_300),
+        ("123456.00", SupportedCurrency::EUR, 12_345_600),
+        ("123456,00", SupportedCurrency::EUR, 12_345_600),
         // no decimals, with separators
-        ("123.456", 123_456.0),
-        ("123,456", 123_456.0),
-        ("123.456.789", 123_456_789.0),
-        ("123,456,789", 123_456_789.0),
+        ("123.456", SupportedCurrency::EUR, 12_345_600),
+        ("123,456", SupportedCurrency::EUR, 12_345_600),
+        ("123.456.789", SupportedCurrency::EUR, 12_345_678_900),
+        ("123,456,789", SupportedCurrency::EUR, 12_345_678_900),
         // with decimals, with separators
-        ("123.456,00", 123_456.0),
-        ("123,456.00", 123_456.0),
-        ("123.456.789,00", 123_456_789.0),
-        ("123,456,789.00", 123_456_789.0),
+        ("123.456,00", SupportedCurrency::EUR, 12_345_600),
+        ("123,456.00", SupportedCurrency::EUR, 12_345_600),
+        ("123.456.789,00", SupportedCurrency::EUR, 12_345_678_900),
+        ("123,456,789.00", SupportedCurrency::EUR, 12_345_678_900),
+        ("19.99", SupportedCurrency::USD, 1_999),
+        // currencies without minor unit
+        ("9,800", SupportedCurrency::JPY, 9_800),
+        ("123.45", SupportedCurrency::JPY, 123),
+        ("123.50", SupportedCurrency::JPY, 124),
     ];
 
-    for (test_value, expected) in test_values {
+    for (test_value, currency, expected) in test_values {
         assert_eq!(
-            format!(
-                "{:.2}",
-                CurrencyGuesser::get_currency_value(test_value.to_string()).unwrap()
-            ),
-            format!("{:.2}", expected)
+            CurrencyGuesser::get_currency_value(test_value.to_string(), currency.clone()).unwrap(),
+            Money::new(expected, currency)
         );
     }
 }
+
+#[test]
+fn test_money() {
+    use crate::currency::money::Money;
+
+    let price = Money::from_major(81.67, SupportedCurrency::EUR);
+    assert_eq!(price.amount, 8_167);
+    assert_eq!(price.to_string(), "81.67 EUR");
+    assert_eq!(
+        Money::new(9_800, SupportedCurrency::JPY).to_string(),
+        "9800 JPY"
+    );
+
+    // 19% taxes on price and shipping
+    let total = (price + Money::from_major(40.0, SupportedCurrency::EUR)).multiply(1.19);
+    assert_eq!(total, Money::new(14_479, SupportedCurrency::EUR));
+
+    assert!(Money::new(100, SupportedCurrency::EUR) < Money::new(101, SupportedCurrency::EUR));
+    // different currencies are not comparable
+    assert_eq!(
+        Money::new(100, SupportedCurrency::EUR)
+            .partial_cmp(&Money::new(100, SupportedCurrency::JPY)),
+        None
+    );
+}
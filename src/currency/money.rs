@@ -0,0 +1,83 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Display;
+use std::ops::Add;
+
+use crate::currency::SupportedCurrency;
+
+/// Money is an exact amount in the minor unit of its currency (f.e. cents for EUR, yen for JPY),
+/// floating point values are only used at the edges (parsing, conversion rates and output)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Money {
+    pub(crate) amount: i64,
+    pub(crate) currency: SupportedCurrency,
+}
+
+impl Money {
+    /// create money from the passed amount in the minor unit of the currency
+    pub fn new(amount: i64, currency: SupportedCurrency) -> Self {
+        Money { amount, currency }
+    }
+
+    /// create money without any value in the passed currency
+    pub fn zero(currency: SupportedCurrency) -> Self {
+        Money::new(0, currency)
+    }
+
+    /// create money from the passed value in the major unit of the currency,
+    /// the value is rounded to the minor unit
+    pub fn from_major(value: f64, currency: SupportedCurrency) -> Self {
+        let factor = currency.get_minor_factor() as f64;
+        Money::new((value * factor).round() as i64, currency)
+    }
+
+    /// retrieve the value in the major unit of the currency
+    pub fn to_major(&self) -> f64 {
+        self.amount as f64 / self.currency.get_minor_factor() as f64
+    }
+
+    /// multiply the amount with the passed factor (f.e. taxes), rounded to the minor unit
+    pub fn multiply(&self, factor: f64) -> Self {
+        Money::new(
+            (self.amount as f64 * factor).round() as i64,
+            self.currency.clone(),
+        )
+    }
+}
+
+/// adding money of different currencies is a programming error and panics
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        assert_eq!(
+            self.currency, other.currency,
+            "unable to add money of different currencies"
+        );
+        Money::new(self.amount + other.amount, self.currency)
+    }
+}
+
+/// money is only comparable within the same currency
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Money) -> Option<Ordering> {
+        if self.currency == other.currency {
+            self.amount.partial_cmp(&other.amount)
+        } else {
+            None
+        }
+    }
+}
+
+/// display the amount with the decimal places of the currency and the currency code
+impl Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.*} {}",
+            self.currency.get_exponent() as usize,
+            self.to_major(),
+            self.currency
+        )
+    }
+}
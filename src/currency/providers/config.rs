@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use chrono::Utc;
+
+use crate::currency::providers::ExchangeRateProvider;
+use crate::currency::SupportedCurrency;
+use crate::database::exchange_rates::ExchangeRate;
+
+/// ConfigRates provides the static exchange rates defined in the configuration file
+pub(crate) struct ConfigRates<'a> {
+    rates: &'a BTreeMap<SupportedCurrency, f64>,
+}
+
+impl<'a> ConfigRates<'a> {
+    /// create new instance of the static exchange rate provider
+    pub fn new(rates: &'a BTreeMap<SupportedCurrency, f64>) -> Self {
+        ConfigRates { rates }
+    }
+}
+
+impl ExchangeRateProvider for ConfigRates<'_> {
+    fn get_provider_key(&self) -> String {
+        "static".to_string()
+    }
+
+    /// retrieve the configured exchange rates, valid as of today
+    fn get_exchange_rates(&self) -> Result<Vec<ExchangeRate>, Box<dyn Error>> {
+        let timestamp = Utc::now();
+
+        Ok(self
+            .rates
+            .iter()
+            .map(|(currency, rate)| ExchangeRate {
+                currency: currency.to_string(),
+                rate: *rate,
+                reference_date: timestamp.naive_utc().date(),
+                timestamp,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,199 @@
+use std::error::Error;
+
+use chrono::{Duration, NaiveDate, Utc};
+use clap::ArgEnum;
+use serde::Deserialize;
+
+use crate::currency::guesser::CurrencyGuesser;
+use crate::currency::providers::ExchangeRateProvider;
+use crate::database::exchange_rates::{ExchangeRate, ExchangeRates};
+use crate::database::Database;
+use crate::http::Client;
+
+/// structs used for the deserialization of the EUR reference values of the ECB
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(rename = "Cube")]
+    pub cube: Cube,
+}
+
+#[derive(Deserialize)]
+struct Cube {
+    /// the daily feed contains only the latest date, the history feeds one entry per date
+    #[serde(rename = "Cube")]
+    pub times: Vec<CubeTime>,
+}
+
+#[derive(Deserialize)]
+struct CubeTime {
+    #[serde(rename = "time")]
+    pub date: String,
+    #[serde(rename = "Cube")]
+    pub currencies: Vec<CubeCurrency>,
+}
+
+#[derive(Deserialize)]
+struct CubeCurrency {
+    pub currency: String,
+    pub rate: String,
+}
+
+/// the available feeds of the ECB euro reference rates
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ExchangeRateFeed {
+    /// rates of the latest working day
+    Daily,
+    /// rates of the last 90 days
+    Recent,
+    /// all rates since 1999
+    Full,
+}
+
+impl ExchangeRateFeed {
+    /// retrieve the URL of the XML feed
+    pub fn get_url(&self) -> &'static str {
+        match self {
+            ExchangeRateFeed::Daily => {
+                "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml"
+            }
+            ExchangeRateFeed::Recent => {
+                "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist-90d.xml"
+            }
+            ExchangeRateFeed::Full => {
+                "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist.xml"
+            }
+        }
+    }
+}
+
+/// custom error if the response of the ECB couldn't be parsed
+#[derive(Debug)]
+pub(crate) struct InvalidExchangeRatesError {
+    msg: String,
+}
+
+impl std::fmt::Display for InvalidExchangeRatesError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "invalid exchange rates from the ECB: {}", self.msg)
+    }
+}
+
+impl Error for InvalidExchangeRatesError {}
+
+/// Ecb provides the euro reference rates of the European Central Bank,
+/// the rates are cached in the database and only retrieved again after the TTL expired
+pub(crate) struct Ecb<'a> {
+    client: &'a Client,
+    db: &'a Database,
+    ttl: Duration,
+}
+
+impl<'a> Ecb<'a> {
+    /// create new instance of the ECB exchange rate provider
+    pub fn new(client: &'a Client, db: &'a Database, ttl: Duration) -> Self {
+        Ecb { client, db, ttl }
+    }
+
+    /// retrieve the conversion rates of the passed ECB euro reference feed,
+    /// rates of currencies which are not supported (anymore) are skipped
+    pub fn get_feed_rates(
+        client: &Client,
+        feed: ExchangeRateFeed,
+    ) -> Result<Vec<ExchangeRate>, Box<dyn Error>> {
+        let res = client.get(feed.get_url()).send()?;
+        let envelope: Envelope =
+            quick_xml::de::from_str(&res.text()?).map_err(|err| InvalidExchangeRatesError {
+                msg: err.to_string(),
+            })?;
+
+        let currency_guesser = CurrencyGuesser::new();
+        let timestamp = Utc::now();
+        let mut exchange_rates = vec![];
+
+        for time in envelope.cube.times.iter() {
+            let reference_date = NaiveDate::parse_from_str(time.date.as_str(), "%Y-%m-%d")
+                .map_err(|err| InvalidExchangeRatesError {
+                    msg: format!("invalid date {:?}: {}", time.date, err),
+                })?;
+
+            for currency in time.currencies.iter() {
+                if currency_guesser
+                    .guess_currency_from_code(currency.currency.clone(), true)
+                    .is_none()
+                {
+                    continue;
+                }
+
+                exchange_rates.push(ExchangeRate {
+                    currency: currency.currency.clone(),
+                    rate: currency.rate.parse()?,
+                    reference_date,
+                    timestamp,
+                });
+            }
+        }
+
+        let dates = exchange_rates.iter().map(|rate| rate.reference_date);
+        if let (Some(oldest), Some(latest)) = (dates.clone().min(), dates.max()) {
+            if oldest == latest {
+                info!("extracted exchange rates from date: {}", latest);
+            } else {
+                info!(
+                    "extracted exchange rates from date: {} to {}",
+                    oldest, latest
+                );
+            }
+        }
+
+        Ok(exchange_rates)
+    }
+}
+
+impl ExchangeRateProvider for Ecb<'_> {
+    fn get_provider_key(&self) -> String {
+        "ecb".to_string()
+    }
+
+    /// retrieve the current exchange rates, the cached exchange rates are used
+    /// if they are not older than the TTL or if the current exchange rates couldn't be retrieved
+    fn get_exchange_rates(&self) -> Result<Vec<ExchangeRate>, Box<dyn Error>> {
+        let cached_rates = self.db.get_latest_exchange_rates().unwrap_or_else(|err| {
+            warn!(
+                "unable to retrieve the cached exchange rates (err: {:?})",
+                err
+            );
+            vec![]
+        });
+
+        if let Some(cached_rate) = cached_rates.first() {
+            if cached_rate.timestamp + self.ttl > Utc::now() {
+                debug!(
+                    "using cached exchange rates from date: {}",
+                    cached_rate.reference_date
+                );
+                return Ok(cached_rates);
+            }
+        }
+
+        match Ecb::get_feed_rates(self.client, ExchangeRateFeed::Daily) {
+            Ok(rates) => {
+                if let Err(err) = self.db.add_exchange_rates(&rates) {
+                    warn!("unable to cache the exchange rates (err: {:?})", err);
+                }
+
+                Ok(rates)
+            }
+            Err(err) => match cached_rates.first() {
+                Some(cached_rate) => {
+                    warn!(
+                        "unable to retrieve the current exchange rates, \
+                         using the last known exchange rates from date: {} (err: {:?})",
+                        cached_rate.reference_date, err
+                    );
+                    Ok(cached_rates)
+                }
+                None => Err(err),
+            },
+        }
+    }
+}
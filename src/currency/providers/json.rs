@@ -0,0 +1,46 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+
+use crate::currency::providers::ExchangeRateProvider;
+use crate::currency::SupportedCurrency;
+use crate::database::exchange_rates::ExchangeRate;
+
+/// JsonFile provides the exchange rates from a JSON file mapping the currency codes to the rates,
+/// f.e. {"TWD": 33.1, "USD": 1.08}, the modification time of the file is used as reference date
+pub(crate) struct JsonFile {
+    path: PathBuf,
+}
+
+impl JsonFile {
+    /// create new instance of the JSON file exchange rate provider
+    pub fn new(path: PathBuf) -> Self {
+        JsonFile { path }
+    }
+}
+
+impl ExchangeRateProvider for JsonFile {
+    fn get_provider_key(&self) -> String {
+        format!("json ({})", self.path.display())
+    }
+
+    /// read the exchange rates from the JSON file
+    fn get_exchange_rates(&self) -> Result<Vec<ExchangeRate>, Box<dyn Error>> {
+        let rates: BTreeMap<SupportedCurrency, f64> =
+            serde_json::from_str(&read_to_string(&self.path)?)?;
+        let timestamp: DateTime<Utc> = self.path.metadata()?.modified()?.into();
+
+        Ok(rates
+            .into_iter()
+            .map(|(currency, rate)| ExchangeRate {
+                currency: currency.to_string(),
+                rate,
+                reference_date: timestamp.naive_utc().date(),
+                timestamp,
+            })
+            .collect())
+    }
+}
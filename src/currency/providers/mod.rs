@@ -0,0 +1,28 @@
+use std::error::Error;
+
+use serde::Deserialize;
+
+use crate::database::exchange_rates::ExchangeRate;
+
+pub(crate) mod config;
+pub(crate) mod ecb;
+pub(crate) mod json;
+
+/// the available exchange rate providers which can be chained in the configuration
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ExchangeRateProviderType {
+    /// euro reference rates of the European Central Bank
+    Ecb,
+    /// static exchange rates from the configuration file
+    Static,
+    /// exchange rates from a JSON file
+    Json,
+}
+
+/// ExchangeRateProvider contains the functionality required from all exchange rate providers,
+/// all rates are the units of the currency equivalent to 1 EUR
+pub(crate) trait ExchangeRateProvider {
+    fn get_provider_key(&self) -> String;
+    fn get_exchange_rates(&self) -> Result<Vec<ExchangeRate>, Box<dyn Error>>;
+}
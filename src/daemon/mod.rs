@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::database::items::Item;
+
+/// flag set by the signal handler once SIGINT or SIGTERM got received
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// signal handler for SIGINT and SIGTERM, only sets the atomic flag to stay async-signal-safe
+extern "C" fn handle_shutdown_signal(_signal: libc::c_int) {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// register the signal handlers for SIGINT and SIGTERM to request a clean shutdown
+pub fn register_signal_handlers() {
+    let handler = handle_shutdown_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+/// check if a shutdown got requested by SIGINT or SIGTERM
+pub fn is_shutdown_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// sleep until the passed instant, returns earlier if a shutdown got requested
+pub fn sleep_until(until: Instant) {
+    while !is_shutdown_requested() {
+        let now = Instant::now();
+        if now >= until {
+            break;
+        }
+
+        thread::sleep(std::cmp::min(until - now, Duration::from_secs(1)));
+    }
+}
+
+/// Scheduler keeps track of when the prices of the tracked items have to be checked again
+pub(crate) struct Scheduler {
+    interval: Duration,
+    jitter: Duration,
+    item_intervals: HashMap<i64, Duration>,
+    next_checks: HashMap<i64, Instant>,
+}
+
+impl Scheduler {
+    /// create a new scheduler with the default interval, the maximum jitter
+    /// and the custom intervals of specific items mapped by their JAN/EAN number
+    pub fn new(
+        interval: Duration,
+        jitter: Duration,
+        item_intervals: HashMap<i64, Duration>,
+    ) -> Self {
+        Scheduler {
+            interval,
+            jitter,
+            item_intervals,
+            next_checks: HashMap::new(),
+        }
+    }
+
+    /// check if the prices of the passed item have to be checked,
+    /// items which got never checked before are always due
+    pub fn is_due(&self, item: &Item, now: Instant) -> bool {
+        match self.next_checks.get(&item.id) {
+            Some(next_check) => *next_check <= now,
+            None => true,
+        }
+    }
+
+    /// schedule the next check of the passed item based on its interval and a random jitter
+    pub fn schedule(&mut self, item: &Item, now: Instant) {
+        let next_check = now + self.get_interval(item) + self.get_jitter();
+        self.next_checks.insert(item.id, next_check);
+    }
+
+    /// retrieve the earliest scheduled check
+    pub fn get_next_check(&self) -> Option<Instant> {
+        self.next_checks.values().min().cloned()
+    }
+
+    /// retrieve the interval of the passed item, falls back to the default interval
+    fn get_interval(&self, item: &Item) -> Duration {
+        match self.item_intervals.get(&item.jan) {
+            Some(interval) => *interval,
+            None => self.interval,
+        }
+    }
+
+    /// retrieve a pseudo random delay between 0 and the configured jitter
+    fn get_jitter(&self) -> Duration {
+        if self.jitter.as_millis() == 0 {
+            return Duration::from_millis(0);
+        }
+
+        // no cryptographic requirements here, the sub second nanos are random enough
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.subsec_nanos())
+            .unwrap_or(0) as u128;
+
+        Duration::from_millis((nanos % self.jitter.as_millis()) as u64)
+    }
+}
+
+#[test]
+fn test_scheduler() {
+    let item = Item {
+        id: 1,
+        jan: 4_580_416_940_283,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+
+    let mut custom_item = item.clone();
+    custom_item.id = 2;
+    custom_item.jan = 4_934_054_783_441;
+
+    let mut item_intervals = HashMap::new();
+    item_intervals.insert(custom_item.jan, Duration::from_secs(600));
+
+    let mut scheduler = Scheduler::new(
+        Duration::from_secs(3600),
+        Duration::from_secs(0),
+        item_intervals,
+    );
+
+    let now = Instant::now();
+    assert!(scheduler.is_due(&item, now));
+    assert!(scheduler.is_due(&custom_item, now));
+
+    scheduler.schedule(&item, now);
+    scheduler.schedule(&custom_item, now);
+    assert!(!scheduler.is_due(&item, now + Duration::from_secs(600)));
+    assert!(scheduler.is_due(&custom_item, now + Duration::from_secs(600)));
+    assert!(scheduler.is_due(&item, now + Duration::from_secs(3600)));
+    assert_eq!(
+        scheduler.get_next_check(),
+        Some(now + Duration::from_secs(600))
+    );
+}
+
+#[test]
+fn test_scheduler_jitter() {
+    let scheduler = Scheduler::new(
+        Duration::from_secs(3600),
+        Duration::from_secs(60),
+        HashMap::new(),
+    );
+
+    for _ in 0..10 {
+        assert!(scheduler.get_jitter() < Duration::from_secs(60));
+    }
+}
@@ -1,11 +1,55 @@
+use core::fmt;
 use std::error::Error;
+use std::fmt::Formatter;
 
-use rusqlite::params;
+use chrono::Duration;
+use clap::ArgEnum;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, Row, ToSql};
 
 use crate::conditions::ConditionType;
-use crate::database::items::{Item, ItemConditions};
+use crate::database::items::{Grade, Item, ItemConditions};
+use crate::database::prices::{Availability, Price};
 use crate::database::Database;
 
+/// PreOrders defines if the pre-orders of not yet released items are checked by a condition
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PreOrders {
+    Include,
+    Exclude,
+    Only,
+}
+
+impl std::fmt::Display for PreOrders {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PreOrders::Include => write!(f, "include"),
+            PreOrders::Exclude => write!(f, "exclude"),
+            PreOrders::Only => write!(f, "only"),
+        }
+    }
+}
+
+/// implementation for the ToSql trait for the rusqlite dependency
+impl ToSql for PreOrders {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+/// implementation for the FromSql trait for the rusqlite dependency
+/// to only allow the known pre-order options
+impl FromSql for PreOrders {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str().and_then(|s| match s {
+            "include" => Ok(PreOrders::Include),
+            "exclude" => Ok(PreOrders::Exclude),
+            "only" => Ok(PreOrders::Only),
+            _ => Err(FromSqlError::InvalidType),
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct Condition {
     pub(crate) id: Option<i64>,
@@ -13,6 +57,15 @@ pub(crate) struct Condition {
     pub(crate) condition_type: ConditionType,
     pub(crate) item_condition: ItemConditions,
     pub(crate) value: f64,
+    /// the minimum grade of the item itself
+    pub(crate) min_item_grade: Option<Grade>,
+    /// the minimum grade of the box
+    pub(crate) min_box_grade: Option<Grade>,
+    pub(crate) pre_orders: PreOrders,
+    /// only the buyout price of auctions is compared, auctions without buyout price never match
+    pub(crate) buyout_only: bool,
+    /// only auctions ending within the hours match
+    pub(crate) ending_within: Option<u32>,
     pub(crate) disabled: bool,
 }
 
@@ -29,55 +82,196 @@ impl Condition {
             condition_type: notification_type,
             item_condition: condition,
             value,
+            min_item_grade: None,
+            min_box_grade: None,
+            pre_orders: PreOrders::Include,
+            buyout_only: false,
+            ending_within: None,
             disabled: false,
         }
     }
+
+    /// retrieve the condition from a selected row
+    fn from_row(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        Ok(Condition {
+            id: row.get(0)?,
+            item_id: row.get(1)?,
+            condition_type: row.get(2)?,
+            value: row.get(3)?,
+            item_condition: row.get(4)?,
+            disabled: row.get(5)?,
+            min_item_grade: row.get(6)?,
+            min_box_grade: row.get(7)?,
+            pre_orders: row.get(8)?,
+            buyout_only: row.get(9)?,
+            ending_within: row.get(10)?,
+        })
+    }
+
+    /// check if the condition targets auctions and is checked against every listing of the item
+    /// instead of the lowest prices only
+    pub fn is_auction_condition(&self) -> bool {
+        self.buyout_only || self.ending_within.is_some()
+    }
+
+    /// retrieve the price the value of the condition is compared to,
+    /// buyout conditions compare the buyout price of auctions and never match auctions without one
+    pub fn get_compared_price(&self, price: &Price) -> Option<Price> {
+        if self.buyout_only && price.is_auction() {
+            price.get_buyout_price()
+        } else {
+            Some(price.clone())
+        }
+    }
+
+    /// check if the item condition, the grades, the availability and the auction end of the price
+    /// meet the requirements,
+    /// new items without grading are mint and used items without grading never meet a minimum grade
+    pub fn matches_price(&self, price: &Price) -> bool {
+        if self.item_condition != ItemConditions::All && self.item_condition != price.condition {
+            return false;
+        }
+
+        if let Some(hours) = self.ending_within {
+            match price.auction_end {
+                Some(auction_end)
+                    if auction_end > price.timestamp
+                        && auction_end - price.timestamp <= Duration::hours(hours as i64) => {}
+                _ => return false,
+            }
+        }
+
+        let is_pre_order = price.availability == Availability::PreOrder;
+        match self.pre_orders {
+            PreOrders::Exclude if is_pre_order => return false,
+            PreOrders::Only if !is_pre_order => return false,
+            _ => {}
+        }
+
+        let default_grade = if price.condition == ItemConditions::New {
+            Some(Grade::S)
+        } else {
+            None
+        };
+        let meets_grade = |min_grade: Option<Grade>, grade: Option<Grade>| match min_grade {
+            Some(min_grade) => grade
+                .or(default_grade)
+                .map_or(false, |grade| grade >= min_grade),
+            None => true,
+        };
+
+        meets_grade(self.min_item_grade, price.item_grade)
+            && meets_grade(self.min_box_grade, price.box_grade)
+    }
 }
 
 /// Conditions implements all related functionality for conditions to interact with the database
 pub(crate) trait Conditions {
     fn add_condition(&self, condition: Condition) -> Result<(), Box<dyn Error>>;
+    fn get_conditions(&self) -> Result<Vec<Condition>, Box<dyn Error>>;
+    fn get_condition(&self, id: i64) -> Result<Condition, Box<dyn Error>>;
     fn get_related_conditions(&self, item: Item) -> Result<Vec<Condition>, Box<dyn Error>>;
+    fn update_condition(&self, condition: &Condition) -> Result<(), Box<dyn Error>>;
+    fn remove_condition(&self, condition: &Condition) -> Result<(), Box<dyn Error>>;
+}
+
+struct NoSuchConditionFoundError {
+    id: i64,
+}
+
+impl NoSuchConditionFoundError {
+    fn display(&self) -> String {
+        format!("no condition found by ID: {}", self.id)
+    }
+}
+
+impl std::fmt::Display for NoSuchConditionFoundError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display())
+    }
+}
+
+impl std::fmt::Debug for NoSuchConditionFoundError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display())
+    }
 }
 
+impl std::error::Error for NoSuchConditionFoundError {}
+
 impl Conditions for Database {
     fn add_condition(&self, condition: Condition) -> Result<(), Box<dyn Error>> {
         self.conn.execute(
             "INSERT OR IGNORE INTO conditions(
-                    item_id, type, condition, value, disabled
-                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    item_id, type, condition, value, disabled, min_item_grade, min_box_grade,
+                    pre_orders, buyout_only, ending_within
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 condition.item_id,
                 condition.condition_type.to_string(),
                 condition.item_condition,
                 format!("{:.2}", condition.value),
-                condition.disabled
+                condition.disabled,
+                condition.min_item_grade,
+                condition.min_box_grade,
+                condition.pre_orders,
+                condition.buyout_only,
+                condition.ending_within
             ],
         )?;
 
         Ok(())
     }
 
+    /// retrieve all conditions of all items
+    fn get_conditions(&self) -> Result<Vec<Condition>, Box<dyn Error>> {
+        let mut conditions = vec![];
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, item_id, type, value, condition, disabled, min_item_grade, min_box_grade,
+                    pre_orders, buyout_only, ending_within
+            FROM conditions
+            ORDER BY item_id, id",
+        )?;
+
+        let conditions_iter = stmt.query_map(params![], Condition::from_row)?;
+
+        for condition in conditions_iter {
+            conditions.push(condition?);
+        }
+
+        Ok(conditions)
+    }
+
+    /// retrieve a condition from the database based on its ID
+    fn get_condition(&self, id: i64) -> Result<Condition, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, item_id, type, value, condition, disabled, min_item_grade, min_box_grade,
+                    pre_orders, buyout_only, ending_within
+            FROM conditions
+            WHERE id = ?1",
+        )?;
+
+        let mut conditions_iter = stmt.query_map(params![id], Condition::from_row)?;
+
+        match conditions_iter.next() {
+            Some(condition) => Ok(condition?),
+            None => Err(Box::from(NoSuchConditionFoundError { id })),
+        }
+    }
+
     /// retrieve related conditions to the passed item
     fn get_related_conditions(&self, item: Item) -> Result<Vec<Condition>, Box<dyn Error>> {
         let mut conditions = vec![];
 
         let mut stmt = self.conn.prepare(
-            "SELECT id, item_id, type, value, condition, disabled
+            "SELECT id, item_id, type, value, condition, disabled, min_item_grade, min_box_grade,
+                    pre_orders, buyout_only, ending_within
             FROM conditions
             WHERE item_id = ?1",
         )?;
 
-        let conditions_iter = stmt.query_map(params![item.id], |row| {
-            Ok(Condition {
-                id: row.get(0)?,
-                item_id: row.get(1)?,
-                condition_type: row.get(2)?,
-                value: row.get(3)?,
-                item_condition: row.get(4)?,
-                disabled: row.get(5)?,
-            })
-        })?;
+        let conditions_iter = stmt.query_map(params![item.id], Condition::from_row)?;
 
         for condition in conditions_iter {
             conditions.push(condition.unwrap());
@@ -85,4 +279,39 @@ impl Conditions for Database {
 
         Ok(conditions)
     }
+
+    /// synchronize the changes of a mutated condition to the database
+    fn update_condition(&self, condition: &Condition) -> Result<(), Box<dyn Error>> {
+        self.conn.execute(
+            "UPDATE conditions
+                SET type = ?1, condition = ?2, value = ?3, disabled = ?4,
+                    min_item_grade = ?5, min_box_grade = ?6, pre_orders = ?7,
+                    buyout_only = ?8, ending_within = ?9
+                WHERE id = ?10",
+            params![
+                condition.condition_type.to_string(),
+                condition.item_condition,
+                format!("{:.2}", condition.value),
+                condition.disabled,
+                condition.min_item_grade,
+                condition.min_box_grade,
+                condition.pre_orders,
+                condition.buyout_only,
+                condition.ending_within,
+                condition.id
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// remove the condition from the database
+    fn remove_condition(&self, condition: &Condition) -> Result<(), Box<dyn Error>> {
+        self.conn.execute(
+            "DELETE FROM conditions WHERE id = ?1",
+            params![condition.id],
+        )?;
+
+        Ok(())
+    }
 }
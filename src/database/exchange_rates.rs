@@ -0,0 +1,145 @@
+use std::error::Error;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::{params, NO_PARAMS};
+
+use crate::database::Database;
+
+/// ExchangeRate is the EUR reference rate of a currency published for a specific date
+#[derive(Clone, Debug)]
+pub(crate) struct ExchangeRate {
+    pub(crate) currency: String,
+    pub(crate) rate: f64,
+    pub(crate) reference_date: NaiveDate,
+    /// the time the rate was retrieved
+    pub(crate) timestamp: DateTime<Utc>,
+}
+
+/// ExchangeRates implements all related functionality for exchange rates to interact with the database
+pub(crate) trait ExchangeRates {
+    fn add_exchange_rates(&self, rates: &[ExchangeRate]) -> Result<(), Box<dyn Error>>;
+    fn get_latest_exchange_rates(&self) -> Result<Vec<ExchangeRate>, Box<dyn Error>>;
+    fn get_exchange_rates_at(&self, date: NaiveDate) -> Result<Vec<ExchangeRate>, Box<dyn Error>>;
+}
+
+/// maximum days between the requested date and the last published reference date before it,
+/// the ECB doesn't publish rates on weekends and holidays
+const MAX_REFERENCE_DAYS: i64 = 7;
+
+/// ExchangeRates is the implementation of the ExchangeRates trait
+impl ExchangeRates for Database {
+    /// add the passed rates to the database, already existing rates of the same reference date
+    /// are replaced to update the time they were retrieved
+    fn add_exchange_rates(&self, rates: &[ExchangeRate]) -> Result<(), Box<dyn Error>> {
+        self.transaction(|conn| {
+            for rate in rates.iter() {
+                conn.execute(
+                    "INSERT OR REPLACE INTO exchange_rates(currency, rate, reference_date, tstamp)
+                    VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        rate.currency,
+                        rate.rate,
+                        rate.reference_date,
+                        rate.timestamp
+                    ],
+                )?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// retrieve the rates of the most recent reference date
+    fn get_latest_exchange_rates(&self) -> Result<Vec<ExchangeRate>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT currency, rate, reference_date, tstamp
+            FROM exchange_rates
+            WHERE reference_date = (SELECT MAX(reference_date) FROM exchange_rates)
+            ORDER BY currency",
+        )?;
+
+        let rate_iter = stmt.query_map(NO_PARAMS, |row| {
+            Ok(ExchangeRate {
+                currency: row.get(0)?,
+                rate: row.get(1)?,
+                reference_date: row.get(2)?,
+                timestamp: row.get(3)?,
+            })
+        })?;
+
+        let mut rates = vec![];
+        for rate in rate_iter {
+            rates.push(rate?);
+        }
+
+        Ok(rates)
+    }
+
+    /// retrieve the rates which were valid on the passed date,
+    /// which are the rates of the last reference date on or before the passed date
+    fn get_exchange_rates_at(&self, date: NaiveDate) -> Result<Vec<ExchangeRate>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT currency, rate, reference_date, tstamp
+            FROM exchange_rates
+            WHERE reference_date = (
+                SELECT MAX(reference_date)
+                FROM exchange_rates
+                WHERE reference_date <= ?1 AND reference_date >= ?2
+            )
+            ORDER BY currency",
+        )?;
+
+        let rate_iter = stmt.query_map(
+            params![date, date - Duration::days(MAX_REFERENCE_DAYS)],
+            |row| {
+                Ok(ExchangeRate {
+                    currency: row.get(0)?,
+                    rate: row.get(1)?,
+                    reference_date: row.get(2)?,
+                    timestamp: row.get(3)?,
+                })
+            },
+        )?;
+
+        let mut rates = vec![];
+        for rate in rate_iter {
+            rates.push(rate?);
+        }
+
+        Ok(rates)
+    }
+}
+
+#[test]
+fn test_get_exchange_rates_at() {
+    let db = Database::open(":memory:").unwrap();
+    let rate = |date: &str, rate: f64| ExchangeRate {
+        currency: "JPY".to_string(),
+        rate,
+        reference_date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+        timestamp: Utc::now(),
+    };
+
+    db.add_exchange_rates(&[
+        rate("2020-02-12", 120.45),
+        rate("2020-02-13", 119.38),
+        rate("2020-02-14", 119.07),
+    ])
+    .unwrap();
+
+    let get_rate = |date: &str| {
+        db.get_exchange_rates_at(NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap())
+            .unwrap()
+            .first()
+            .map(|rate| rate.rate)
+    };
+
+    assert_eq!(get_rate("2020-02-13"), Some(119.38));
+    // no rates are published on weekends, so the rates of the friday are used
+    assert_eq!(get_rate("2020-02-16"), Some(119.07));
+    // rates from before the history or too old rates are not used
+    assert_eq!(get_rate("2020-02-11"), None);
+    assert_eq!(get_rate("2020-03-01"), None);
+
+    assert_eq!(db.get_latest_exchange_rates().unwrap()[0].rate, 119.07);
+}
@@ -20,7 +20,7 @@ pub(crate) struct Item {
 }
 
 /// Available item conditions to request for
-#[derive(ArgEnum, Clone, Copy, Debug)]
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
 pub(crate) enum ItemConditions {
     New,
     Used,
@@ -81,12 +81,79 @@ impl FromSql for ItemConditions {
     }
 }
 
+/// Grade is the normalized grading of the item or its box which modules map the grading
+/// of the shops into, the grades are ordered from the worst to the best grade
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Grade {
+    /// damaged, incomplete or missing
+    Junk,
+    /// noticeable signs of usage
+    C,
+    /// minor signs of usage
+    B,
+    /// nearly mint condition
+    A,
+    /// mint and unopened condition
+    S,
+}
+
+impl Grade {
+    /// retrieve the grade from the common letter grading of japanese shops, f.e. "A-" or "B+"
+    pub fn from_letter(letter: &str) -> Option<Self> {
+        match letter.trim().trim_end_matches(['+', '-']) {
+            "S" => Some(Grade::S),
+            "A" => Some(Grade::A),
+            "B" => Some(Grade::B),
+            "C" => Some(Grade::C),
+            "J" => Some(Grade::Junk),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Grade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Grade::Junk => write!(f, "junk"),
+            Grade::C => write!(f, "c"),
+            Grade::B => write!(f, "b"),
+            Grade::A => write!(f, "a"),
+            Grade::S => write!(f, "s"),
+        }
+    }
+}
+
+/// implementation for the ToSql trait for the rusqlite dependency
+impl ToSql for Grade {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+/// implementation for the FromSql trait for the rusqlite dependency
+/// to only allow the known grades
+impl FromSql for Grade {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str().and_then(|s| match s {
+            "junk" => Ok(Grade::Junk),
+            "c" => Ok(Grade::C),
+            "b" => Ok(Grade::B),
+            "a" => Ok(Grade::A),
+            "s" => Ok(Grade::S),
+            _ => Err(FromSqlError::InvalidType),
+        })
+    }
+}
+
 /// Items implements all related functionality for items to interact with the database
 pub(crate) trait Items {
     fn get_items(&self) -> Result<Vec<Item>, Box<dyn Error>>;
+    fn get_all_items(&self) -> Result<Vec<Item>, Box<dyn Error>>;
     fn get_item(&self, jan: i64) -> Result<Item, Box<dyn Error>>;
     fn add_item(&self, jan: i64) -> Result<Item, Box<dyn Error>>;
     fn update_item(&self, item: &Item) -> Result<(), Box<dyn Error>>;
+    fn archive_item(&self, item: &Item) -> Result<(), Box<dyn Error>>;
+    fn remove_item(&self, item: &Item) -> Result<(), Box<dyn Error>>;
 }
 
 struct NoSuchItemFoundError {
@@ -142,6 +209,34 @@ impl Items for Database {
         Ok(items)
     }
 
+    /// retrieve all items from the database including the disabled items
+    fn get_all_items(&self) -> Result<Vec<Item>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, jan, term_en, term_jp, description, image, disabled
+             FROM tracked_items
+             ORDER BY id",
+        )?;
+
+        let res = stmt.query_map(params![], |row| {
+            Ok(Item {
+                id: row.get(0)?,
+                jan: row.get(1)?,
+                term_en: row.get(2)?,
+                term_jp: row.get(3)?,
+                description: row.get(4)?,
+                image: row.get(5)?,
+                disabled: row.get(6)?,
+            })
+        })?;
+
+        let mut items: Vec<Item> = vec![];
+        for item in res {
+            items.push(item?)
+        }
+
+        Ok(items)
+    }
+
     /// retrieve an item from the database based on their JAN number
     fn get_item(&self, jan: i64) -> Result<Item, Box<dyn Error>> {
         let mut stmt = self.conn.prepare(
@@ -199,4 +294,35 @@ impl Items for Database {
 
         Ok(())
     }
+
+    /// disable the item and all of its conditions while keeping the recorded prices
+    fn archive_item(&self, item: &Item) -> Result<(), Box<dyn Error>> {
+        self.transaction(|conn| {
+            conn.execute(
+                "UPDATE tracked_items SET disabled = 1 WHERE id = ?1",
+                params![item.id],
+            )?;
+            conn.execute(
+                "UPDATE conditions SET disabled = 1 WHERE item_id = ?1",
+                params![item.id],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// remove the item including all related notifications, prices, listings, sales and conditions
+    fn remove_item(&self, item: &Item) -> Result<(), Box<dyn Error>> {
+        self.transaction(|conn| {
+            for table in ["notifications", "prices", "listings", "sales", "conditions"].iter() {
+                conn.execute(
+                    format!("DELETE FROM {} WHERE item_id = ?1", table).as_str(),
+                    params![item.id],
+                )?;
+            }
+            conn.execute("DELETE FROM tracked_items WHERE id = ?1", params![item.id])?;
+
+            Ok(())
+        })
+    }
 }
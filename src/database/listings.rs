@@ -0,0 +1,448 @@
+use std::error::Error;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Row};
+
+use crate::conditions::ConditionType;
+use crate::currency::money::Money;
+use crate::database::conditions::Condition;
+use crate::database::prices::Price;
+use crate::database::Database;
+
+/// ListingEvent is the change of a listing compared to the previous checks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ListingEvent {
+    New,
+    BackInStock,
+}
+
+/// Listing is a single offer of a module including the offers which aren't the lowest price
+#[derive(Clone, Debug)]
+pub(crate) struct Listing {
+    pub(crate) id: Option<i64>,
+    /// the price of the offer, the timestamp of the price is the time the listing was last seen
+    pub(crate) price: Price,
+    /// the seller of marketplace offers, empty if the shop sells the item itself
+    pub(crate) seller: String,
+    /// the grading of the condition as displayed by the shop
+    pub(crate) grade: String,
+    pub(crate) in_stock: bool,
+    pub(crate) first_seen: DateTime<Utc>,
+    /// the time the listing disappeared or went out of stock, probably because it got sold
+    pub(crate) sold_out_at: Option<DateTime<Utc>>,
+    /// the module reported the listing as sold, the price is the realized price of a sale
+    /// and the listing is recorded as sale instead of as listing
+    pub(crate) sold: bool,
+}
+
+impl Listing {
+    /// retrieve a listing of the passed price which is currently in stock
+    pub fn new(price: Price, seller: String, grade: String) -> Self {
+        Listing {
+            id: None,
+            first_seen: price.timestamp,
+            price,
+            seller,
+            grade,
+            in_stock: true,
+            sold_out_at: None,
+            sold: false,
+        }
+    }
+
+    /// retrieve the time the listing was last seen
+    pub fn get_last_seen(&self) -> DateTime<Utc> {
+        self.price.timestamp
+    }
+
+    /// retrieve the time the listing was available until it sold out
+    pub fn get_lifetime(&self) -> Option<Duration> {
+        self.sold_out_at
+            .map(|sold_out_at| sold_out_at - self.first_seen)
+    }
+
+    /// check if both listings are the same offer
+    fn is_same_offer(&self, other: &Listing) -> bool {
+        self.price.item_id == other.price.item_id
+            && self.price.module == other.price.module
+            && self.price.url == other.price.url
+            && self.price.condition == other.price.condition
+            && self.seller == other.seller
+    }
+
+    /// detect the event of the listing compared to the previously recorded listings of the item,
+    /// all listings of the first check of a module are already known and no new listings
+    pub fn get_event(&self, known_listings: &[Listing]) -> Option<ListingEvent> {
+        if !self.in_stock {
+            return None;
+        }
+
+        match known_listings
+            .iter()
+            .find(|known_listing| self.is_same_offer(known_listing))
+        {
+            Some(known_listing) if known_listing.sold_out_at.is_some() => {
+                Some(ListingEvent::BackInStock)
+            }
+            Some(_) => None,
+            None => {
+                if known_listings
+                    .iter()
+                    .any(|known_listing| known_listing.price.module == self.price.module)
+                {
+                    Some(ListingEvent::New)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// check if the event of the listing matches the passed condition,
+    /// the condition value is the maximum full price of the listing or 0 for any price
+    pub fn matches_condition(&self, event: ListingEvent, condition: &Condition) -> bool {
+        let condition_event = match condition.condition_type {
+            ConditionType::NewListing => ListingEvent::New,
+            ConditionType::BackInStock => ListingEvent::BackInStock,
+            _ => return false,
+        };
+
+        if condition_event != event {
+            return false;
+        }
+
+        if !condition.matches_price(&self.price) {
+            return false;
+        }
+
+        // auctions are compared by their buyout price for buyout conditions
+        let price = match condition.get_compared_price(&self.price) {
+            Some(price) => price,
+            None => return false,
+        };
+
+        condition.value <= 0.0
+            || price.get_converted_total()
+                < Money::from_major(condition.value, price.converted_price.currency.clone())
+    }
+
+    /// retrieve the listing from a selected row with the price columns in the order
+    /// of the prices table followed by the listing specific columns
+    fn from_row(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        let mut price = Price::from_row(row)?;
+        let id = price.id.take();
+
+        Ok(Listing {
+            id,
+            price,
+            seller: row.get(21)?,
+            grade: row.get(22)?,
+            in_stock: row.get(23)?,
+            first_seen: row.get(24)?,
+            sold_out_at: row.get(25)?,
+            sold: false,
+        })
+    }
+}
+
+/// Listings implements all related functionality for listings to interact with the database
+pub(crate) trait Listings {
+    fn record_listing(&self, listing: &Listing) -> Result<i64, Box<dyn Error>>;
+    fn mark_sold_out_listings(
+        &self,
+        item_id: i64,
+        module: &str,
+        checked_at: DateTime<Utc>,
+    ) -> Result<usize, Box<dyn Error>>;
+    fn get_listings(
+        &self,
+        item_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Listing>, Box<dyn Error>>;
+}
+
+/// Listings is the implementation of the Listings trait
+impl Listings for Database {
+    /// add the listing or update the already known listing of the same offer
+    /// and return the ID of the listing, the first seen timestamp is kept on updates
+    /// and the sold out timestamp is kept until the listing is available again
+    fn record_listing(&self, listing: &Listing) -> Result<i64, Box<dyn Error>> {
+        let price = &listing.price;
+        let sold_out_at = if listing.in_stock {
+            None
+        } else {
+            Some(listing.sold_out_at.unwrap_or(price.timestamp))
+        };
+
+        self.conn.execute(
+            "INSERT INTO listings(
+                    item_id, price, currency, converted_price, converted_currency, taxes,
+                    shipping, url, module, condition, last_seen, fees, duty, clearance_fee,
+                    seller, grade, in_stock, first_seen, sold_out_at, item_grade, box_grade,
+                    availability, release_month, buyout_price, auction_end
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14,
+                    ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)
+                ON CONFLICT (item_id, module, url, condition, seller) DO UPDATE SET
+                    price = excluded.price,
+                    currency = excluded.currency,
+                    converted_price = excluded.converted_price,
+                    converted_currency = excluded.converted_currency,
+                    taxes = excluded.taxes,
+                    shipping = excluded.shipping,
+                    last_seen = excluded.last_seen,
+                    fees = excluded.fees,
+                    duty = excluded.duty,
+                    clearance_fee = excluded.clearance_fee,
+                    grade = excluded.grade,
+                    item_grade = excluded.item_grade,
+                    box_grade = excluded.box_grade,
+                    availability = excluded.availability,
+                    release_month = excluded.release_month,
+                    buyout_price = excluded.buyout_price,
+                    auction_end = excluded.auction_end,
+                    in_stock = excluded.in_stock,
+                    sold_out_at = CASE WHEN excluded.in_stock THEN NULL
+                        ELSE COALESCE(listings.sold_out_at, excluded.sold_out_at) END",
+            params![
+                price.item_id,
+                price.price.amount,
+                price.price.currency,
+                price.converted_price.amount,
+                price.converted_price.currency,
+                price.taxes,
+                price.shipping.amount,
+                price.url,
+                price.module,
+                price.condition,
+                price.timestamp,
+                price.fees.amount,
+                price.duty.amount,
+                price.clearance_fee.amount,
+                listing.seller,
+                listing.grade,
+                listing.in_stock,
+                listing.first_seen,
+                sold_out_at,
+                price.item_grade,
+                price.box_grade,
+                price.availability,
+                price.release_month,
+                price
+                    .buyout_price
+                    .as_ref()
+                    .map(|buyout_price| buyout_price.amount),
+                price.auction_end
+            ],
+        )?;
+
+        // the last inserted row ID isn't updated if the listing was already known
+        Ok(self.conn.query_row(
+            "SELECT id FROM listings
+            WHERE item_id = ?1 AND module = ?2 AND url = ?3 AND condition = ?4 AND seller = ?5",
+            params![
+                price.item_id,
+                price.module,
+                price.url,
+                price.condition,
+                listing.seller
+            ],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// mark the listings of the module which weren't seen during the check as sold out
+    /// and return the number of newly sold out listings
+    fn mark_sold_out_listings(
+        &self,
+        item_id: i64,
+        module: &str,
+        checked_at: DateTime<Utc>,
+    ) -> Result<usize, Box<dyn Error>> {
+        Ok(self.conn.execute(
+            "UPDATE listings SET sold_out_at = ?3
+            WHERE item_id = ?1
+                AND module = ?2
+                AND last_seen < ?3
+                AND sold_out_at IS NULL",
+            params![item_id, module, checked_at],
+        )?)
+    }
+
+    /// retrieve the recorded listings, optionally limited to an item
+    /// and to listings seen since the passed date, the cheapest listings are returned first
+    fn get_listings(
+        &self,
+        item_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, item_id, price, currency, converted_price, converted_currency, taxes,
+                    shipping, url, module, condition, last_seen, fees, duty, clearance_fee,
+                    item_grade, box_grade, availability, release_month, buyout_price, auction_end,
+                    seller, grade, in_stock, first_seen, sold_out_at
+            FROM listings
+            WHERE (?1 IS NULL OR item_id = ?1)
+                AND (?2 IS NULL OR last_seen >= ?2)
+            ORDER BY item_id, converted_price, module",
+        )?;
+
+        let listing_iter = stmt.query_map(params![item_id, since], Listing::from_row)?;
+
+        let mut listings = vec![];
+        for listing in listing_iter {
+            listings.push(listing?);
+        }
+
+        Ok(listings)
+    }
+}
+
+#[test]
+fn test_record_listings() {
+    use crate::currency::SupportedCurrency;
+    use crate::database::items::{ItemConditions, Items};
+
+    let db = Database::open(":memory:").unwrap();
+    let item = db.add_item(4_580_416_940_283).unwrap();
+
+    let first_seen = Utc::now() - Duration::days(2);
+    let get_listing = |value: i64, seller: &str, timestamp: DateTime<Utc>| {
+        let mut price = Price::new(
+            item.clone(),
+            Money::new(value, SupportedCurrency::JPY),
+            "https://myfigurecollection.net/classified/201001".to_string(),
+            "myfigurecollection.net".to_string(),
+            ItemConditions::Used,
+        );
+        price.timestamp = timestamp;
+        Listing::new(price, seller.to_string(), "".to_string())
+    };
+
+    let id = db
+        .record_listing(&get_listing(9800, "seller", first_seen))
+        .unwrap();
+    db.record_listing(&get_listing(12000, "other seller", first_seen))
+        .unwrap();
+
+    // the same offer seen again updates the price and keeps the first seen timestamp
+    let mut listing = get_listing(9500, "seller", Utc::now());
+    listing.in_stock = false;
+    assert_eq!(db.record_listing(&listing).unwrap(), id);
+
+    let listings = db.get_listings(Some(item.id), None).unwrap();
+    assert_eq!(listings.len(), 2);
+    let listing = listings
+        .iter()
+        .find(|listing| listing.id == Some(id))
+        .unwrap();
+    assert_eq!(listing.price.price.amount, 9500);
+    assert_eq!(listing.first_seen, first_seen);
+    assert!(!listing.in_stock);
+    assert_eq!(listing.sold_out_at, Some(listing.get_last_seen()));
+
+    let since = Utc::now() - Duration::days(1);
+    assert_eq!(db.get_listings(None, Some(since)).unwrap().len(), 1);
+}
+
+#[test]
+fn test_listing_events() {
+    use crate::configuration::ListingCosts;
+    use crate::currency::SupportedCurrency;
+    use crate::database::items::{ItemConditions, Items};
+
+    let db = Database::open(":memory:").unwrap();
+    let item = db.add_item(4_580_416_940_283).unwrap();
+
+    let first_check = Utc::now() - Duration::days(3);
+    let get_listing = |url: &str, module: &str, timestamp: DateTime<Utc>| {
+        let mut price = Price::new(
+            item.clone(),
+            Money::new(9800, SupportedCurrency::JPY),
+            url.to_string(),
+            module.to_string(),
+            ItemConditions::Used,
+        );
+        price.set_conversion(
+            Money::new(8000, SupportedCurrency::EUR),
+            &ListingCosts::default(),
+        );
+        price.timestamp = timestamp;
+        Listing::new(price, "".to_string(), "".to_string())
+    };
+
+    for url in ["classified/1", "classified/2"].iter() {
+        db.record_listing(&get_listing(url, "myfigurecollection.net", first_check))
+            .unwrap();
+    }
+
+    // listings which weren't seen anymore during the check of the module are sold out
+    let second_check = first_check + Duration::days(2);
+    db.record_listing(&get_listing(
+        "classified/1",
+        "myfigurecollection.net",
+        second_check,
+    ))
+    .unwrap();
+    assert_eq!(
+        db.mark_sold_out_listings(item.id, "myfigurecollection.net", second_check)
+            .unwrap(),
+        1
+    );
+    let known_listings = db.get_listings(Some(item.id), None).unwrap();
+    let sold_out = known_listings
+        .iter()
+        .find(|listing| listing.price.url == "classified/2")
+        .unwrap();
+    assert_eq!(sold_out.get_lifetime(), Some(Duration::days(2)));
+
+    let now = Utc::now();
+    let new_listing = get_listing("classified/3", "myfigurecollection.net", now);
+    let relisted = get_listing("classified/2", "myfigurecollection.net", now);
+    assert_eq!(
+        new_listing.get_event(&known_listings),
+        Some(ListingEvent::New)
+    );
+    assert_eq!(
+        relisted.get_event(&known_listings),
+        Some(ListingEvent::BackInStock)
+    );
+    assert_eq!(
+        get_listing("classified/1", "myfigurecollection.net", now).get_event(&known_listings),
+        None
+    );
+    // the listings of modules without previous listings are all known after the first check
+    assert_eq!(
+        get_listing("item/1", "amiami.com", now).get_event(&known_listings),
+        None
+    );
+
+    // the condition value is the maximum full price of the listing
+    let condition = |condition_type: ConditionType, value: f64| {
+        Condition::new(condition_type, ItemConditions::All, value, item.id)
+    };
+    assert!(new_listing.matches_condition(
+        ListingEvent::New,
+        &condition(ConditionType::NewListing, 0.0)
+    ));
+    assert!(new_listing.matches_condition(
+        ListingEvent::New,
+        &condition(ConditionType::NewListing, 90.0)
+    ));
+    assert!(!new_listing.matches_condition(
+        ListingEvent::New,
+        &condition(ConditionType::NewListing, 70.0)
+    ));
+    assert!(!new_listing.matches_condition(
+        ListingEvent::New,
+        &condition(ConditionType::BackInStock, 0.0)
+    ));
+    assert!(!new_listing.matches_condition(
+        ListingEvent::New,
+        &Condition::new(ConditionType::NewListing, ItemConditions::New, 0.0, item.id)
+    ));
+
+    // listings which are available again are no longer sold out
+    db.record_listing(&relisted).unwrap();
+    let listings = db.get_listings(Some(item.id), None).unwrap();
+    assert!(listings.iter().all(|listing| listing.sold_out_at.is_none()));
+}
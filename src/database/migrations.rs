@@ -1,19 +1,141 @@
-use rusqlite::{Error, NO_PARAMS};
+use std::error::Error;
+
+use rusqlite::NO_PARAMS;
 
 use crate::database::Database;
 
+/// MigrationStep is a single versioned change of the database structure
+/// with the statements to apply (up) and to revert (down) the change
+pub(crate) struct MigrationStep {
+    pub(crate) version: u32,
+    pub(crate) description: &'static str,
+    up: fn(&Database) -> Result<(), rusqlite::Error>,
+    down: fn(&Database) -> Result<(), rusqlite::Error>,
+}
+
+/// all migration steps ordered by their version, the version of the last step is the
+/// schema version required by the application and is stored in the user_version pragma
+pub(crate) static MIGRATIONS: &[MigrationStep] = &[
+    MigrationStep {
+        version: 1,
+        description: "create tracked items, prices, conditions and notifications",
+        up: create_base_tables,
+        down: drop_base_tables,
+    },
+    MigrationStep {
+        version: 2,
+        description: "record condition, channel and result of notifications",
+        up: add_notification_results,
+        down: remove_notification_results,
+    },
+    MigrationStep {
+        version: 3,
+        description: "cache the exchange rates of the ECB",
+        up: create_exchange_rates,
+        down: drop_exchange_rates,
+    },
+    MigrationStep {
+        version: 4,
+        description: "store prices in the minor unit of their currency",
+        up: convert_prices_to_minor_units,
+        down: convert_prices_to_major_units,
+    },
+    MigrationStep {
+        version: 5,
+        description: "record service fees and payment surcharges of prices",
+        up: add_price_fees,
+        down: remove_price_fees,
+    },
+    MigrationStep {
+        version: 6,
+        description: "record customs duty and clearance fees of prices",
+        up: add_price_import_costs,
+        down: remove_price_import_costs,
+    },
+    MigrationStep {
+        version: 7,
+        description: "record all listings of the modules",
+        up: create_listings,
+        down: drop_listings,
+    },
+    MigrationStep {
+        version: 8,
+        description: "record when listings sold out",
+        up: add_listing_sold_out,
+        down: remove_listing_sold_out,
+    },
+    MigrationStep {
+        version: 9,
+        description: "record item and box grades and minimum grades of conditions",
+        up: add_grades,
+        down: remove_grades,
+    },
+    MigrationStep {
+        version: 10,
+        description: "record the availability of prices and pre-order options of conditions",
+        up: add_availability,
+        down: remove_availability,
+    },
+    MigrationStep {
+        version: 11,
+        description:
+            "record the buyout price and end of auctions and auction options of conditions",
+        up: add_auctions,
+        down: remove_auctions,
+    },
+    MigrationStep {
+        version: 12,
+        description: "record the realized prices of sold listings",
+        up: create_sales,
+        down: drop_sales,
+    },
+];
+
+/// custom error if the database was migrated by a newer version of the application
+#[derive(Debug)]
+pub(crate) struct UnsupportedSchemaVersionError {
+    version: u32,
+}
+
+impl std::fmt::Display for UnsupportedSchemaVersionError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "database schema version {} is newer than the latest known version {}",
+            self.version,
+            get_latest_version()
+        )
+    }
+}
+
+impl Error for UnsupportedSchemaVersionError {}
+
+/// retrieve the schema version the latest migration step migrates to
+pub(crate) fn get_latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |step| step.version)
+}
+
 /// Migration implements the functionality to migrate the database to the required structure
 pub trait Migration {
-    fn uses_wal_journal(&self) -> Result<bool, Error>;
-    fn set_wal_journal_mode(&self) -> Result<(), Error>;
-    fn create_migrations(&self) -> Result<(), Error>;
+    fn uses_wal_journal(&self) -> Result<bool, rusqlite::Error>;
+    fn set_wal_journal_mode(&self) -> Result<(), rusqlite::Error>;
+    fn has_column(&self, table: &str, column: &str) -> Result<bool, rusqlite::Error>;
+    fn add_missing_column(
+        &self,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<(), rusqlite::Error>;
+    fn get_schema_version(&self) -> Result<u32, rusqlite::Error>;
+    fn migrate(&self, version: u32) -> Result<(), Box<dyn Error>>;
+    fn create_migrations(&self) -> Result<(), Box<dyn Error>>;
 }
 
 /// Migration is the implementation of the Migration trait
 impl Migration for Database {
     /// check if the current connection is using the WAL journal mode
-    fn uses_wal_journal(&self) -> Result<bool, Error> {
-        let res: Result<String, Error>;
+    fn uses_wal_journal(&self) -> Result<bool, rusqlite::Error> {
+        let res: Result<String, rusqlite::Error>;
 
         res = self
             .conn
@@ -23,70 +145,567 @@ impl Migration for Database {
     }
 
     /// sets the WAL journal mode
-    fn set_wal_journal_mode(&self) -> Result<(), Error> {
+    fn set_wal_journal_mode(&self) -> Result<(), rusqlite::Error> {
         self.conn.execute_batch("PRAGMA journal_mode=WAL")
     }
 
-    /// create the required tables and alters updated/missing columns on updates
-    fn create_migrations(&self) -> Result<(), Error> {
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS tracked_items
-                (
-                    id          INTEGER PRIMARY KEY AUTOINCREMENT,
-                    jan         INTEGER NOT NULL UNIQUE DEFAULT '0',
-                    term_en     VARCHAR(255)            DEFAULT '',
-                    term_jp     VARCHAR(255)            DEFAULT '',
-                    description VARCHAR(255)            DEFAULT '',
-                    image       VARCHAR(255)            DEFAULT '',
-                    disabled    BOOLEAN                 DEFAULT FALSE NOT NULL
-                )",
-            NO_PARAMS,
+    /// check if the passed table already contains the passed column
+    fn has_column(&self, table: &str, column: &str) -> Result<bool, rusqlite::Error> {
+        let mut stmt = self
+            .conn
+            .prepare(format!("PRAGMA table_info({})", table).as_str())?;
+        let mut columns = stmt.query_map(NO_PARAMS, |row| row.get::<_, String>(1))?;
+
+        Ok(columns.any(|name| name.map_or(false, |name| name == column)))
+    }
+
+    /// add the passed column to the table if it doesn't exist yet
+    fn add_missing_column(
+        &self,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<(), rusqlite::Error> {
+        if !self.has_column(table, column)? {
+            self.conn.execute_batch(
+                format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition).as_str(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// retrieve the current schema version of the database, 0 for new databases
+    fn get_schema_version(&self) -> Result<u32, rusqlite::Error> {
+        self.conn
+            .query_row("PRAGMA user_version", NO_PARAMS, |row| row.get::<_, i64>(0))
+            .map(|version| version as u32)
+    }
+
+    /// apply or revert the migration steps until the database has the passed schema version,
+    /// every step is applied together with its version inside of a transaction
+    fn migrate(&self, version: u32) -> Result<(), Box<dyn Error>> {
+        let current = self.get_schema_version()?;
+        if current > get_latest_version() {
+            return Err(Box::from(UnsupportedSchemaVersionError {
+                version: current,
+            }));
+        }
+
+        if version > get_latest_version() {
+            return Err(Box::from(UnsupportedSchemaVersionError { version }));
+        }
+
+        // apply the missing steps in ascending order
+        for step in MIGRATIONS
+            .iter()
+            .filter(|step| step.version > current && step.version <= version)
+        {
+            info!("applying migration {}: {}", step.version, step.description);
+            self.transaction(|conn| {
+                (step.up)(self)?;
+                conn.execute_batch(format!("PRAGMA user_version = {}", step.version).as_str())
+            })?;
+        }
+
+        // revert the exceeding steps in descending order
+        for (index, step) in MIGRATIONS.iter().enumerate().rev() {
+            if step.version <= version || step.version > current {
+                continue;
+            }
+
+            info!("reverting migration {}: {}", step.version, step.description);
+            let previous = if index > 0 {
+                MIGRATIONS[index - 1].version
+            } else {
+                0
+            };
+            self.transaction(|conn| {
+                (step.down)(self)?;
+                conn.execute_batch(format!("PRAGMA user_version = {}", previous).as_str())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// apply all pending migration steps to create or update the database structure
+    fn create_migrations(&self) -> Result<(), Box<dyn Error>> {
+        self.migrate(get_latest_version())
+    }
+}
+
+/// create the base tables, existing tables of databases created before the
+/// versioned migrations are kept as they are
+fn create_base_tables(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute(
+        "CREATE TABLE IF NOT EXISTS tracked_items
+            (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                jan         INTEGER NOT NULL UNIQUE DEFAULT '0',
+                term_en     VARCHAR(255)            DEFAULT '',
+                term_jp     VARCHAR(255)            DEFAULT '',
+                description VARCHAR(255)            DEFAULT '',
+                image       VARCHAR(255)            DEFAULT '',
+                disabled    BOOLEAN                 DEFAULT FALSE NOT NULL
+            )",
+        NO_PARAMS,
+    )?;
+
+    db.conn.execute(
+        "CREATE TABLE IF NOT EXISTS prices
+            (
+                id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+                item_id            INTEGER        NOT NULL REFERENCES tracked_items (id),
+                price              DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                currency           VARCHAR(255)            DEFAULT '',
+                converted_price    DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                converted_currency VARCHAR(255)            DEFAULT '',
+                taxes              DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                shipping           DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                url                VARCHAR(255)            DEFAULT '',
+                module             VARCHAR(255)            DEFAULT '',
+                condition          VARCHAR(255)            DEFAULT '',
+                tstamp             TIMESTAMP               DEFAULT CURRENT_TIMESTAMP
+            )",
+        NO_PARAMS,
+    )?;
+
+    db.conn.execute(
+        "CREATE TABLE IF NOT EXISTS conditions
+            (
+                id        INTEGER PRIMARY KEY AUTOINCREMENT,
+                item_id   INTEGER        NOT NULL REFERENCES tracked_items (id),
+                type      VARCHAR(255)            DEFAULT '',
+                value     DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                condition VARCHAR(255)            DEFAULT NULL,
+                disabled  BOOLEAN        NOT NULL DEFAULT FALSE
+            )",
+        NO_PARAMS,
+    )?;
+
+    db.conn.execute(
+        "CREATE TABLE IF NOT EXISTS notifications
+            (
+                id       INTEGER PRIMARY KEY AUTOINCREMENT,
+                type     VARCHAR(255) DEFAULT '',
+                tstamp   TIMESTAMP    DEFAULT CURRENT_TIMESTAMP,
+                item_id  INTEGER NOT NULL REFERENCES tracked_items (id),
+                price_id INTEGER NOT NULL REFERENCES prices (id)
+            )",
+        NO_PARAMS,
+    )?;
+
+    Ok(())
+}
+
+/// drop all base tables
+fn drop_base_tables(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch(
+        "DROP TABLE IF EXISTS notifications;
+        DROP TABLE IF EXISTS conditions;
+        DROP TABLE IF EXISTS prices;
+        DROP TABLE IF EXISTS tracked_items;",
+    )
+}
+
+/// columns to record which condition triggered the notification and the dispatch result,
+/// databases created before the versioned migrations may already contain these columns
+fn add_notification_results(db: &Database) -> Result<(), rusqlite::Error> {
+    db.add_missing_column(
+        "notifications",
+        "condition_id",
+        "INTEGER DEFAULT NULL REFERENCES conditions (id)",
+    )?;
+    db.add_missing_column("notifications", "channel", "VARCHAR(255) DEFAULT ''")?;
+    db.add_missing_column("notifications", "success", "BOOLEAN NOT NULL DEFAULT FALSE")?;
+    db.add_missing_column("notifications", "error", "TEXT DEFAULT ''")?;
+
+    Ok(())
+}
+
+/// remove the notification result columns, the bundled SQLite version doesn't support
+/// dropping columns yet, so the table is recreated with the remaining columns
+fn remove_notification_results(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch(
+        "ALTER TABLE notifications RENAME TO notifications_old;
+        CREATE TABLE notifications
+            (
+                id       INTEGER PRIMARY KEY AUTOINCREMENT,
+                type     VARCHAR(255) DEFAULT '',
+                tstamp   TIMESTAMP    DEFAULT CURRENT_TIMESTAMP,
+                item_id  INTEGER NOT NULL REFERENCES tracked_items (id),
+                price_id INTEGER NOT NULL REFERENCES prices (id)
+            );
+        INSERT INTO notifications (id, type, tstamp, item_id, price_id)
+            SELECT id, type, tstamp, item_id, price_id FROM notifications_old;
+        DROP TABLE notifications_old;",
+    )
+}
+
+/// create the table for the exchange rates including the date they are valid for
+fn create_exchange_rates(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch(
+        "CREATE TABLE exchange_rates
+            (
+                id             INTEGER PRIMARY KEY AUTOINCREMENT,
+                currency       VARCHAR(255) NOT NULL,
+                rate           DOUBLE       NOT NULL,
+                reference_date DATE         NOT NULL,
+                tstamp         TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (currency, reference_date)
+            );",
+    )
+}
+
+/// drop the exchange rates table
+fn drop_exchange_rates(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn
+        .execute_batch("DROP TABLE IF EXISTS exchange_rates;")
+}
+
+/// factor of the minor unit of the passed currency column, currencies without minor unit
+/// are listed explicitly to keep the migration independent from later currency changes
+fn get_minor_unit_factor(column: &str) -> String {
+    format!(
+        "(CASE WHEN {} IN ('JPY', 'KRW', 'ISK') THEN 1 ELSE 100 END)",
+        column
+    )
+}
+
+/// convert the recorded decimal values into integer amounts of the minor unit,
+/// the shipping costs are in the currency of the converted price
+fn convert_prices_to_minor_units(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch(
+        format!(
+            "UPDATE prices SET
+                price = CAST(ROUND(price * {factor}) AS INTEGER),
+                converted_price = CAST(ROUND(converted_price * {converted_factor}) AS INTEGER),
+                shipping = CAST(ROUND(shipping * {converted_factor}) AS INTEGER);",
+            factor = get_minor_unit_factor("currency"),
+            converted_factor = get_minor_unit_factor("converted_currency"),
+        )
+        .as_str(),
+    )
+}
+
+/// convert the integer amounts of the minor unit back into decimal values
+fn convert_prices_to_major_units(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch(
+        format!(
+            "UPDATE prices SET
+                price = price * 1.0 / {factor},
+                converted_price = converted_price * 1.0 / {converted_factor},
+                shipping = shipping * 1.0 / {converted_factor};",
+            factor = get_minor_unit_factor("currency"),
+            converted_factor = get_minor_unit_factor("converted_currency"),
+        )
+        .as_str(),
+    )
+}
+
+/// column for the service fees and payment surcharges in the minor unit of the converted price
+fn add_price_fees(db: &Database) -> Result<(), rusqlite::Error> {
+    db.add_missing_column("prices", "fees", "DECIMAL(10, 2) NOT NULL DEFAULT '0'")
+}
+
+/// the fees column is kept, the bundled SQLite version doesn't support dropping columns yet
+/// and recreating the prices table would violate the references of the notifications,
+/// previous versions don't select the column and its default value keeps their inserts working
+fn remove_price_fees(_db: &Database) -> Result<(), rusqlite::Error> {
+    Ok(())
+}
+
+/// columns for the customs duty and clearance fee in the minor unit of the converted price
+fn add_price_import_costs(db: &Database) -> Result<(), rusqlite::Error> {
+    db.add_missing_column("prices", "duty", "DECIMAL(10, 2) NOT NULL DEFAULT '0'")?;
+    db.add_missing_column(
+        "prices",
+        "clearance_fee",
+        "DECIMAL(10, 2) NOT NULL DEFAULT '0'",
+    )
+}
+
+/// the import cost columns are kept for the same reasons as the fees column
+fn remove_price_import_costs(_db: &Database) -> Result<(), rusqlite::Error> {
+    Ok(())
+}
+
+/// create the table for all offers of the modules, an offer is identified by the seller
+/// and the condition of the listing since not every shop has distinct URLs per offer
+fn create_listings(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch(
+        "CREATE TABLE listings
+            (
+                id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+                item_id            INTEGER        NOT NULL REFERENCES tracked_items (id),
+                price              DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                currency           VARCHAR(255)            DEFAULT '',
+                converted_price    DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                converted_currency VARCHAR(255)            DEFAULT '',
+                taxes              DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                shipping           DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                fees               DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                duty               DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                clearance_fee      DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                url                VARCHAR(255)   NOT NULL DEFAULT '',
+                module             VARCHAR(255)   NOT NULL DEFAULT '',
+                condition          VARCHAR(255)   NOT NULL DEFAULT '',
+                seller             VARCHAR(255)   NOT NULL DEFAULT '',
+                grade              VARCHAR(255)   NOT NULL DEFAULT '',
+                in_stock           BOOLEAN        NOT NULL DEFAULT TRUE,
+                first_seen         TIMESTAMP               DEFAULT CURRENT_TIMESTAMP,
+                last_seen          TIMESTAMP               DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (item_id, module, url, condition, seller)
+            );",
+    )
+}
+
+/// drop the listings table
+fn drop_listings(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch("DROP TABLE IF EXISTS listings;")
+}
+
+/// column for the time a listing disappeared or went out of stock,
+/// listings which are already out of stock sold out when they were last seen
+fn add_listing_sold_out(db: &Database) -> Result<(), rusqlite::Error> {
+    db.add_missing_column("listings", "sold_out_at", "TIMESTAMP DEFAULT NULL")?;
+    db.conn
+        .execute_batch("UPDATE listings SET sold_out_at = last_seen WHERE in_stock = 0;")
+}
+
+/// recreate the listings table without the sold out column,
+/// no other table references the listings so the table can be rebuilt
+fn remove_listing_sold_out(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn
+        .execute_batch("ALTER TABLE listings RENAME TO listings_old;")?;
+    create_listings(db)?;
+    db.conn.execute_batch(
+        "INSERT INTO listings (
+                id, item_id, price, currency, converted_price, converted_currency, taxes,
+                shipping, fees, duty, clearance_fee, url, module, condition, seller, grade,
+                in_stock, first_seen, last_seen
+            )
+            SELECT id, item_id, price, currency, converted_price, converted_currency, taxes,
+                shipping, fees, duty, clearance_fee, url, module, condition, seller, grade,
+                in_stock, first_seen, last_seen
+            FROM listings_old;
+        DROP TABLE listings_old;",
+    )
+}
+
+/// columns for the grades of the item and the box of prices and listings
+/// and for the minimum grades of conditions, unknown grades are NULL
+fn add_grades(db: &Database) -> Result<(), rusqlite::Error> {
+    for table in ["prices", "listings"].iter() {
+        db.add_missing_column(table, "item_grade", "VARCHAR(255) DEFAULT NULL")?;
+        db.add_missing_column(table, "box_grade", "VARCHAR(255) DEFAULT NULL")?;
+    }
+    db.add_missing_column("conditions", "min_item_grade", "VARCHAR(255) DEFAULT NULL")?;
+    db.add_missing_column("conditions", "min_box_grade", "VARCHAR(255) DEFAULT NULL")
+}
+
+/// the grade columns are kept since the prices and conditions are referenced by the notifications
+/// and can't be recreated, unset grades don't restrict older versions of the application
+fn remove_grades(_db: &Database) -> Result<(), rusqlite::Error> {
+    Ok(())
+}
+
+/// columns for the availability and the release month of pre-orders of prices and listings
+/// and for the handling of pre-orders by conditions, previous prices were all in stock
+fn add_availability(db: &Database) -> Result<(), rusqlite::Error> {
+    for table in ["prices", "listings"].iter() {
+        db.add_missing_column(
+            table,
+            "availability",
+            "VARCHAR(255) NOT NULL DEFAULT 'in_stock'",
         )?;
+        db.add_missing_column(table, "release_month", "DATE DEFAULT NULL")?;
+    }
+    db.add_missing_column(
+        "conditions",
+        "pre_orders",
+        "VARCHAR(255) NOT NULL DEFAULT 'include'",
+    )
+}
 
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS prices
-                (
-                    id                 INTEGER PRIMARY KEY AUTOINCREMENT,
-                    item_id            INTEGER        NOT NULL REFERENCES tracked_items (id),
-                    price              DECIMAL(10, 2) NOT NULL DEFAULT '0',
-                    currency           VARCHAR(255)            DEFAULT '',
-                    converted_price    DECIMAL(10, 2) NOT NULL DEFAULT '0',
-                    converted_currency VARCHAR(255)            DEFAULT '',
-                    taxes              DECIMAL(10, 2) NOT NULL DEFAULT '0',
-                    shipping           DECIMAL(10, 2) NOT NULL DEFAULT '0',
-                    url                VARCHAR(255)            DEFAULT '',
-                    module             VARCHAR(255)            DEFAULT '',
-                    condition          VARCHAR(255)            DEFAULT '',
-                    tstamp             TIMESTAMP               DEFAULT CURRENT_TIMESTAMP
-                )",
+/// the availability columns are kept for the same reason as the grade columns,
+/// older versions of the application ignore them and conditions include all pre-orders again
+fn remove_availability(_db: &Database) -> Result<(), rusqlite::Error> {
+    Ok(())
+}
+
+/// columns for the buyout price and the end of auctions of prices and listings
+/// and for the auction options of conditions
+fn add_auctions(db: &Database) -> Result<(), rusqlite::Error> {
+    for table in ["prices", "listings"].iter() {
+        db.add_missing_column(table, "buyout_price", "INTEGER DEFAULT NULL")?;
+        db.add_missing_column(table, "auction_end", "TIMESTAMP DEFAULT NULL")?;
+    }
+    db.add_missing_column(
+        "conditions",
+        "buyout_only",
+        "BOOLEAN NOT NULL DEFAULT FALSE",
+    )?;
+    db.add_missing_column("conditions", "ending_within", "INTEGER DEFAULT NULL")
+}
+
+/// the auction columns are kept like the grade columns, older versions of the application
+/// compare the current bid of auctions and ignore the auction options of conditions
+fn remove_auctions(_db: &Database) -> Result<(), rusqlite::Error> {
+    Ok(())
+}
+
+/// create the table for the sold listings of marketplaces which show the realized prices,
+/// a sale is identified by its URL since every sold listing has its own page
+fn create_sales(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch(
+        "CREATE TABLE sales
+            (
+                id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+                item_id            INTEGER        NOT NULL REFERENCES tracked_items (id),
+                price              DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                currency           VARCHAR(255)            DEFAULT '',
+                converted_price    DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                converted_currency VARCHAR(255)            DEFAULT '',
+                taxes              DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                shipping           DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                fees               DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                duty               DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                clearance_fee      DECIMAL(10, 2) NOT NULL DEFAULT '0',
+                url                VARCHAR(255)   NOT NULL DEFAULT '',
+                module             VARCHAR(255)   NOT NULL DEFAULT '',
+                condition          VARCHAR(255)   NOT NULL DEFAULT '',
+                item_grade         VARCHAR(255)            DEFAULT NULL,
+                box_grade          VARCHAR(255)            DEFAULT NULL,
+                availability       VARCHAR(255)   NOT NULL DEFAULT 'in_stock',
+                release_month      DATE                    DEFAULT NULL,
+                buyout_price       INTEGER                 DEFAULT NULL,
+                auction_end        TIMESTAMP               DEFAULT NULL,
+                seller             VARCHAR(255)   NOT NULL DEFAULT '',
+                recorded_at        TIMESTAMP               DEFAULT CURRENT_TIMESTAMP,
+                sold_at            TIMESTAMP               DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (item_id, module, url)
+            );",
+    )
+}
+
+/// drop the sales table
+fn drop_sales(db: &Database) -> Result<(), rusqlite::Error> {
+    db.conn.execute_batch("DROP TABLE IF EXISTS sales;")
+}
+
+#[cfg(test)]
+fn open_fixture_database() -> Database {
+    let db = Database {
+        conn: rusqlite::Connection::open_in_memory().unwrap(),
+    };
+    db.conn
+        .execute_batch(include_str!("../../fixtures/database/baseline.sql"))
+        .unwrap();
+
+    db
+}
+
+#[test]
+fn test_migrate_baseline_database() {
+    let db = open_fixture_database();
+    assert_eq!(db.get_schema_version().unwrap(), 0);
+    assert!(!db.has_column("notifications", "channel").unwrap());
+
+    db.create_migrations().unwrap();
+    assert_eq!(db.get_schema_version().unwrap(), get_latest_version());
+    assert!(db.has_column("notifications", "condition_id").unwrap());
+    assert!(db.has_column("notifications", "channel").unwrap());
+    assert!(db.has_column("notifications", "success").unwrap());
+    assert!(db.has_column("notifications", "error").unwrap());
+    assert!(db.has_column("prices", "fees").unwrap());
+    assert!(db.has_column("prices", "clearance_fee").unwrap());
+    assert!(db.has_column("listings", "sold_out_at").unwrap());
+    assert!(db.has_column("prices", "item_grade").unwrap());
+    assert!(db.has_column("conditions", "min_box_grade").unwrap());
+    assert!(db.has_column("prices", "availability").unwrap());
+    assert!(db.has_column("conditions", "pre_orders").unwrap());
+    assert!(db.has_column("listings", "auction_end").unwrap());
+    assert!(db.has_column("conditions", "ending_within").unwrap());
+    assert!(db.has_column("sales", "sold_at").unwrap());
+
+    // the existing data is kept and the new columns use their default values
+    let (items, prices): (i64, i64) = db
+        .conn
+        .query_row(
+            "SELECT (SELECT COUNT(*) FROM tracked_items), (SELECT COUNT(*) FROM prices)",
             NO_PARAMS,
-        )?;
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((items, prices), (2, 3));
 
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS conditions
-                (
-                    id        INTEGER PRIMARY KEY AUTOINCREMENT,
-                    item_id   INTEGER        NOT NULL REFERENCES tracked_items (id),
-                    type      VARCHAR(255)            DEFAULT '',
-                    value     DECIMAL(10, 2) NOT NULL DEFAULT '0',
-                    condition VARCHAR(255)            DEFAULT NULL,
-                    disabled  BOOLEAN        NOT NULL DEFAULT FALSE
-                )",
+    let (channel, success): (String, bool) = db
+        .conn
+        .query_row(
+            "SELECT channel, success FROM notifications WHERE id = 1",
             NO_PARAMS,
-        )?;
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(channel, "");
+    assert!(!success);
 
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS notifications
-                (
-                    id       INTEGER PRIMARY KEY AUTOINCREMENT,
-                    type     VARCHAR(255) DEFAULT '',
-                    tstamp   TIMESTAMP    DEFAULT CURRENT_TIMESTAMP,
-                    item_id  INTEGER NOT NULL REFERENCES tracked_items (id),
-                    price_id INTEGER NOT NULL REFERENCES prices (id)
-                )",
+    // the prices are stored in the minor unit of their currency
+    let (price, converted_price): (i64, i64) = db
+        .conn
+        .query_row(
+            "SELECT price, converted_price FROM prices WHERE id = 1",
             NO_PARAMS,
-        )?;
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((price, converted_price), (12800, 10560));
 
-        Ok(())
-    }
+    // applying the migrations again doesn't change anything
+    db.create_migrations().unwrap();
+    assert_eq!(db.get_schema_version().unwrap(), get_latest_version());
+}
+
+#[test]
+fn test_revert_migrations() {
+    let db = open_fixture_database();
+    db.create_migrations().unwrap();
+
+    db.migrate(7).unwrap();
+    assert!(db.has_column("listings", "first_seen").unwrap());
+    assert!(!db.has_column("listings", "sold_out_at").unwrap());
+
+    db.migrate(4).unwrap();
+    assert_eq!(db.get_schema_version().unwrap(), 4);
+
+    db.migrate(3).unwrap();
+    let converted_price: f64 = db
+        .conn
+        .query_row(
+            "SELECT converted_price FROM prices WHERE id = 1",
+            NO_PARAMS,
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(converted_price, 105.6);
+
+    db.migrate(1).unwrap();
+    assert_eq!(db.get_schema_version().unwrap(), 1);
+    assert!(!db.has_column("notifications", "channel").unwrap());
+    let notifications: i64 = db
+        .conn
+        .query_row("SELECT COUNT(*) FROM notifications", NO_PARAMS, |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(notifications, 1);
+
+    db.migrate(0).unwrap();
+    assert_eq!(db.get_schema_version().unwrap(), 0);
+    assert!(!db.has_column("tracked_items", "jan").unwrap());
+
+    // versions newer than the application are refused
+    assert!(db.migrate(get_latest_version() + 1).is_err());
 }
@@ -1,13 +1,19 @@
-use rusqlite::{Connection, Error};
+use std::error::Error;
+use std::path::Path;
+
+use rusqlite::Connection;
 
 use crate::database::migrations::Migration;
 
 // expose database modules
 pub(crate) mod conditions;
+pub(crate) mod exchange_rates;
 pub(crate) mod items;
+pub(crate) mod listings;
 pub(crate) mod migrations;
 pub(crate) mod notifications;
 pub(crate) mod prices;
+pub(crate) mod sales;
 
 /// Database contains the persisting database connection for all database operations
 pub(crate) struct Database {
@@ -17,7 +23,18 @@ pub(crate) struct Database {
 /// Database is the entry point for all database operations
 /// by creating or opening a local database file
 impl Database {
-    pub fn open(path: &str) -> Result<Database, Error> {
+    /// open the database and apply all pending migrations
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Database, Box<dyn Error>> {
+        let db = Database::connect(path)?;
+
+        // create migrations if not already done
+        db.create_migrations()?;
+
+        Ok(db)
+    }
+
+    /// open the database without migrating it, f.e. to check or run the migrations manually
+    pub fn connect<P: AsRef<Path>>(path: P) -> Result<Database, rusqlite::Error> {
         let conn = Connection::open(path)?;
         let db = Database { conn };
 
@@ -27,9 +44,27 @@ impl Database {
             db.set_wal_journal_mode()?;
         }
 
-        // create migrations if not already done
-        db.create_migrations()?;
-
         Ok(db)
     }
+
+    /// execute the passed function inside of a transaction,
+    /// the changes are only committed if the function didn't return an error
+    pub fn transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Connection) -> Result<T, E>,
+        E: From<rusqlite::Error>,
+    {
+        self.conn.execute_batch("BEGIN TRANSACTION")?;
+
+        match f(&self.conn) {
+            Ok(value) => {
+                self.conn.execute_batch("COMMIT")?;
+                Ok(value)
+            }
+            Err(err) => {
+                self.conn.execute_batch("ROLLBACK")?;
+                Err(err)
+            }
+        }
+    }
 }
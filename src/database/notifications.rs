@@ -0,0 +1,212 @@
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+use crate::conditions::ConditionType;
+use crate::database::conditions::Condition;
+use crate::database::prices::Price;
+use crate::database::Database;
+
+/// Notification is the record of a single notification sent over one channel
+#[derive(Clone, Debug)]
+pub(crate) struct Notification {
+    pub(crate) id: Option<i64>,
+    pub(crate) item_id: i64,
+    pub(crate) price_id: i64,
+    pub(crate) condition_id: Option<i64>,
+    pub(crate) condition_type: ConditionType,
+    pub(crate) channel: String,
+    pub(crate) success: bool,
+    pub(crate) error: String,
+    pub(crate) timestamp: DateTime<Utc>,
+}
+
+/// Notifications implements all related functionality for notifications to interact with the database
+pub(crate) trait Notifications {
+    fn add_notification(&self, notification: &Notification) -> Result<i64, Box<dyn Error>>;
+    fn get_notifications(
+        &self,
+        item_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Notification>, Box<dyn Error>>;
+    fn get_last_notified_price(
+        &self,
+        condition: &Condition,
+        price: &Price,
+        channel: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Price>, Box<dyn Error>>;
+}
+
+/// Notifications is the implementation of the Notifications trait
+impl Notifications for Database {
+    /// record a dispatched notification and return the ID of the inserted row
+    fn add_notification(&self, notification: &Notification) -> Result<i64, Box<dyn Error>> {
+        self.conn.execute(
+            "INSERT INTO notifications(
+                    type, tstamp, item_id, price_id, condition_id, channel, success, error
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                notification.condition_type.to_string(),
+                notification.timestamp,
+                notification.item_id,
+                notification.price_id,
+                notification.condition_id,
+                notification.channel,
+                notification.success,
+                notification.error,
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// retrieve the sent notifications, optionally limited to an item and a start date
+    /// the most recent notifications are returned first
+    fn get_notifications(
+        &self,
+        item_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Notification>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, item_id, price_id, condition_id, type, channel, success, error, tstamp
+            FROM notifications
+            WHERE (?1 IS NULL OR item_id = ?1)
+                AND (?2 IS NULL OR tstamp >= ?2)
+            ORDER BY tstamp DESC, id DESC",
+        )?;
+
+        let notification_iter = stmt.query_map(params![item_id, since], |row| {
+            Ok(Notification {
+                id: row.get(0)?,
+                item_id: row.get(1)?,
+                price_id: row.get(2)?,
+                condition_id: row.get(3)?,
+                condition_type: row.get(4)?,
+                channel: row.get(5)?,
+                success: row.get(6)?,
+                error: row.get(7)?,
+                timestamp: row.get(8)?,
+            })
+        })?;
+
+        let mut notifications = vec![];
+        for notification in notification_iter {
+            notifications.push(notification?);
+        }
+
+        Ok(notifications)
+    }
+
+    /// retrieve the price of the last successful notification of the condition over the channel
+    /// for the same listing (module, URL and item condition) since the passed timestamp
+    fn get_last_notified_price(
+        &self,
+        condition: &Condition,
+        price: &Price,
+        channel: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Price>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.id, p.item_id, p.price, p.currency, p.converted_price, p.converted_currency,
+                    p.taxes, p.shipping, p.url, p.module, p.condition, p.tstamp, p.fees,
+                    p.duty, p.clearance_fee, p.item_grade, p.box_grade,
+                    p.availability, p.release_month, p.buyout_price, p.auction_end
+            FROM notifications n
+                INNER JOIN prices p ON p.id = n.price_id
+            WHERE n.condition_id = ?1
+                AND n.channel = ?6
+                AND n.success = 1
+                AND n.tstamp >= ?2
+                AND p.module = ?3
+                AND p.url = ?4
+                AND p.condition = ?5
+            ORDER BY n.tstamp DESC
+            LIMIT 1",
+        )?;
+
+        let mut price_iter = stmt.query_map(
+            params![
+                condition.id,
+                since,
+                price.module,
+                price.url,
+                price.condition,
+                channel
+            ],
+            Price::from_row,
+        )?;
+
+        match price_iter.next() {
+            Some(price) => Ok(Some(price?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[test]
+fn test_last_notified_price() {
+    use chrono::Duration;
+
+    use crate::currency::money::Money;
+    use crate::currency::SupportedCurrency;
+    use crate::database::conditions::Conditions;
+    use crate::database::items::{ItemConditions, Items};
+    use crate::database::prices::Prices;
+
+    let db = Database::open(":memory:").unwrap();
+    let item = db.add_item(4_580_416_940_283).unwrap();
+    db.add_condition(Condition::new(
+        ConditionType::BelowPrice,
+        ItemConditions::All,
+        100.0,
+        item.id,
+    ))
+    .unwrap();
+    let condition = db.get_conditions().unwrap()[0].clone();
+
+    let mut price = Price::new(
+        item.clone(),
+        Money::new(9800, SupportedCurrency::JPY),
+        "https://www.amiami.com/eng/detail/?gcode=FIGURE-123456".to_string(),
+        "amiami.com".to_string(),
+        ItemConditions::New,
+    );
+    price.id = Some(db.add_price(&price).unwrap());
+
+    let get_notification = |channel: &str, success: bool| Notification {
+        id: None,
+        item_id: item.id,
+        price_id: price.id.unwrap(),
+        condition_id: condition.id,
+        condition_type: condition.condition_type,
+        channel: channel.to_string(),
+        success,
+        error: "".to_string(),
+        timestamp: Utc::now(),
+    };
+    db.add_notification(&get_notification("telegram", true))
+        .unwrap();
+    db.add_notification(&get_notification("discord", false))
+        .unwrap();
+
+    // only the channel which successfully notified about the price is considered notified,
+    // so the failed channel is notified again
+    let since = Utc::now() - Duration::hours(1);
+    let last_price = db
+        .get_last_notified_price(&condition, &price, "telegram", since)
+        .unwrap();
+    assert_eq!(last_price.unwrap().price, price.price);
+    assert!(db
+        .get_last_notified_price(&condition, &price, "discord", since)
+        .unwrap()
+        .is_none());
+
+    // notifications before the cooldown are ignored
+    let since = Utc::now() + Duration::hours(1);
+    assert!(db
+        .get_last_notified_price(&condition, &price, "telegram", since)
+        .unwrap()
+        .is_none());
+}
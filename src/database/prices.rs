@@ -1,29 +1,90 @@
 use core::fmt;
 use std::error::Error;
 
-use chrono::{DateTime, Utc};
-use rusqlite::params;
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, Row, ToSql};
 use std::fmt::Formatter;
 
 use crate::conditions::ConditionType;
+use crate::configuration::ListingCosts;
+use crate::currency::money::Money;
 use crate::currency::SupportedCurrency;
 use crate::database::conditions::Condition;
-use crate::database::items::{Item, ItemConditions};
+use crate::database::items::{Grade, Item, ItemConditions};
 use crate::database::Database;
 
+/// Availability of the offer at the time the price was checked
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Availability {
+    InStock,
+    /// the item isn't released yet and can be pre-ordered
+    PreOrder,
+    /// the item is released but has to be ordered by the shop first
+    Backorder,
+}
+
+impl std::fmt::Display for Availability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Availability::InStock => write!(f, "in_stock"),
+            Availability::PreOrder => write!(f, "pre_order"),
+            Availability::Backorder => write!(f, "backorder"),
+        }
+    }
+}
+
+/// implementation for the ToSql trait for the rusqlite dependency
+impl ToSql for Availability {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+/// implementation for the FromSql trait for the rusqlite dependency
+/// to only allow the known availabilities
+impl FromSql for Availability {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str().and_then(|s| match s {
+            "in_stock" => Ok(Availability::InStock),
+            "pre_order" => Ok(Availability::PreOrder),
+            "backorder" => Ok(Availability::Backorder),
+            _ => Err(FromSqlError::InvalidType),
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct Price {
     pub(crate) id: Option<i64>,
     pub(crate) item_id: i64,
-    pub(crate) price: f64,
-    pub(crate) currency: String,
-    pub(crate) converted_price: f64,
-    pub(crate) converted_currency: String,
+    pub(crate) price: Money,
+    pub(crate) converted_price: Money,
     pub(crate) taxes: f64,
-    pub(crate) shipping: f64,
+    /// shipping costs in the currency of the converted price
+    pub(crate) shipping: Money,
+    /// service fees and payment surcharges in the currency of the converted price
+    pub(crate) fees: Money,
+    /// customs duty in the currency of the converted price
+    pub(crate) duty: Money,
+    /// customs clearance fee of the courier in the currency of the converted price
+    pub(crate) clearance_fee: Money,
     pub(crate) url: String,
     pub(crate) module: String,
     pub(crate) condition: ItemConditions,
+    /// the grade of the item itself if the shop grades its items
+    pub(crate) item_grade: Option<Grade>,
+    /// the grade of the box if the shop grades its items
+    pub(crate) box_grade: Option<Grade>,
+    pub(crate) availability: Availability,
+    /// the first day of the release month, the announced release month for pre-orders
+    pub(crate) release_month: Option<NaiveDate>,
+    /// the buy-it-now price of auctions in the currency of the price
+    pub(crate) buyout_price: Option<Money>,
+    /// the end of the auction, the price of auctions is the current bid
+    pub(crate) auction_end: Option<DateTime<Utc>>,
+    /// the costs applied by the last conversion, only known for the prices of the current check
+    pub(crate) costs: ListingCosts,
     pub(crate) timestamp: DateTime<Utc>,
 }
 
@@ -31,8 +92,7 @@ impl Price {
     /// retrieve price from only the required attributes
     pub fn new(
         item: Item,
-        price: f64,
-        currency: SupportedCurrency,
+        price: Money,
         url: String,
         module: String,
         condition: ItemConditions,
@@ -40,35 +100,204 @@ impl Price {
         Price {
             id: None,
             item_id: item.id,
+            converted_price: Money::zero(price.currency.clone()),
+            shipping: Money::zero(price.currency.clone()),
+            fees: Money::zero(price.currency.clone()),
+            duty: Money::zero(price.currency.clone()),
+            clearance_fee: Money::zero(price.currency.clone()),
             price,
-            currency: currency.to_string(),
-            converted_price: 0.0,
-            converted_currency: currency.to_string(),
             taxes: 0.0,
-            shipping: 0.0,
             url,
             module,
             condition,
+            item_grade: None,
+            box_grade: None,
+            availability: Availability::InStock,
+            release_month: None,
+            buyout_price: None,
+            auction_end: None,
+            costs: ListingCosts::default(),
             timestamp: Utc::now(),
         }
     }
 
-    /// retrieve the relevant total of the price
-    pub fn get_converted_total(&self) -> f64 {
-        // shipping costs are normally also taxed
-        (self.converted_price + self.shipping) * (1.0 + self.taxes)
+    /// retrieve the price with the passed grades of the item and its box
+    pub fn with_grades(mut self, item_grade: Option<Grade>, box_grade: Option<Grade>) -> Self {
+        self.item_grade = item_grade;
+        self.box_grade = box_grade;
+        self
+    }
+
+    /// retrieve the price with the passed availability and the release month if known
+    pub fn with_availability(
+        mut self,
+        availability: Availability,
+        release_month: Option<NaiveDate>,
+    ) -> Self {
+        self.availability = availability;
+        self.release_month = release_month;
+        self
+    }
+
+    /// retrieve the price as auction with the current bid as price
+    pub fn with_auction(mut self, buyout_price: Option<Money>, auction_end: DateTime<Utc>) -> Self {
+        self.buyout_price = buyout_price;
+        self.auction_end = Some(auction_end);
+        self
+    }
+
+    /// check if the price is the current bid of an auction
+    pub fn is_auction(&self) -> bool {
+        self.auction_end.is_some()
+    }
+
+    /// retrieve the buy-it-now price of an auction including the costs of the current bid,
+    /// the buyout price is converted with the exchange rate of the current bid
+    pub fn get_buyout_price(&self) -> Option<Price> {
+        let buyout_price = self.buyout_price.clone()?;
+        if self.price.amount <= 0 {
+            return None;
+        }
+
+        let converted_price = self
+            .converted_price
+            .multiply(buyout_price.amount as f64 / self.price.amount as f64);
+        let mut price = self.clone();
+        price.price = buyout_price;
+        price.set_conversion(converted_price, &self.costs);
+
+        Some(price)
+    }
+
+    /// retrieve the end and the buyout price of auctions, f.e. "ends 2024-05-01 20:00 UTC, buyout 12000 JPY"
+    pub fn get_auction_text(&self) -> String {
+        match self.auction_end {
+            Some(auction_end) => format!(
+                "ends {}, buyout {}",
+                auction_end.format("%Y-%m-%d %H:%M UTC"),
+                match &self.buyout_price {
+                    Some(buyout_price) => buyout_price.to_string(),
+                    None => "none".to_string(),
+                }
+            ),
+            None => "none".to_string(),
+        }
+    }
+
+    /// retrieve the availability including the release month of pre-orders, f.e. "pre_order (2024-05)"
+    pub fn get_availability_text(&self) -> String {
+        match self.release_month {
+            Some(release_month) if self.availability == Availability::PreOrder => {
+                format!("{} ({})", self.availability, release_month.format("%Y-%m"))
+            }
+            _ => self.availability.to_string(),
+        }
+    }
+
+    /// retrieve the price from a selected row with the columns in the order of the prices table
+    pub(crate) fn from_row(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        let currency: SupportedCurrency = row.get(3)?;
+        let buyout_price: Option<i64> = row.get(19)?;
+        let buyout_price = buyout_price.map(|amount| Money::new(amount, currency.clone()));
+        let converted_currency: SupportedCurrency = row.get(5)?;
+
+        Ok(Price {
+            id: row.get(0)?,
+            item_id: row.get(1)?,
+            price: Money::new(row.get(2)?, currency),
+            converted_price: Money::new(row.get(4)?, converted_currency.clone()),
+            taxes: row.get(6)?,
+            shipping: Money::new(row.get(7)?, converted_currency.clone()),
+            url: row.get(8)?,
+            module: row.get(9)?,
+            condition: row.get(10)?,
+            timestamp: row.get(11)?,
+            fees: Money::new(row.get(12)?, converted_currency.clone()),
+            duty: Money::new(row.get(13)?, converted_currency.clone()),
+            clearance_fee: Money::new(row.get(14)?, converted_currency),
+            item_grade: row.get(15)?,
+            box_grade: row.get(16)?,
+            availability: row.get(17)?,
+            release_month: row.get(18)?,
+            buyout_price,
+            auction_end: row.get(20)?,
+            costs: ListingCosts::default(),
+        })
+    }
+
+    /// set the converted price and apply the passed costs in the currency of the converted price
+    pub fn set_conversion(&mut self, converted_price: Money, costs: &ListingCosts) {
+        let currency = converted_price.currency.clone();
+        let shipping = Money::from_major(costs.shipping, currency.clone());
+        let fees = converted_price.multiply(costs.service_fee)
+            + Money::from_major(costs.fixed_fee, currency.clone());
+        // the payment surcharge applies to everything paid to the shop or proxy service
+        let surcharge = (converted_price.clone() + fees.clone() + shipping.clone())
+            .multiply(costs.payment_surcharge);
+        let fees = fees + surcharge;
+
+        let mut duty = Money::zero(currency.clone());
+        let mut clearance_fee = Money::zero(currency.clone());
+        let mut taxes = costs.taxes;
+        if let Some(rules) = &costs.import {
+            // the thresholds apply to the value of the item itself
+            if converted_price > Money::from_major(rules.duty_threshold, currency.clone()) {
+                duty = (converted_price.clone() + fees.clone() + shipping.clone())
+                    .multiply(rules.duty);
+            }
+            if converted_price <= Money::from_major(rules.vat_threshold, currency.clone()) {
+                taxes = 0.0;
+            }
+            if duty.amount > 0 || taxes > 0.0 {
+                clearance_fee = Money::from_major(rules.clearance_fee, currency);
+            }
+        }
+
+        self.converted_price = converted_price;
+        self.shipping = shipping;
+        self.fees = fees;
+        self.duty = duty;
+        self.clearance_fee = clearance_fee;
+        self.taxes = taxes;
+        self.costs = costs.clone();
+    }
+
+    /// retrieve the taxes of the price, shipping costs, fees and duty are normally also taxed
+    pub fn get_converted_taxes(&self) -> Money {
+        (self.converted_price.clone()
+            + self.fees.clone()
+            + self.shipping.clone()
+            + self.duty.clone())
+        .multiply(self.taxes)
+    }
+
+    /// retrieve the relevant total of the price including all costs of the import
+    pub fn get_converted_total(&self) -> Money {
+        self.converted_price.clone()
+            + self.fees.clone()
+            + self.shipping.clone()
+            + self.duty.clone()
+            + self.get_converted_taxes()
+            + self.clearance_fee.clone()
     }
 
     /// retrieve the taxed total of the price
-    pub fn get_converted_taxed(&self) -> f64 {
+    pub fn get_converted_taxed(&self) -> Money {
         // taxed amount without shipping costs
-        self.converted_price * (1.0 + self.taxes)
+        (self.converted_price.clone() + self.fees.clone()).multiply(1.0 + self.taxes)
     }
 }
 
 /// Prices implements all related functionality for prices to interact with the database
 pub(crate) trait Prices {
-    fn add_price(&self, price: &Price) -> Result<(), Box<dyn Error>>;
+    fn add_price(&self, price: &Price) -> Result<i64, Box<dyn Error>>;
+    fn get_price(&self, id: i64) -> Result<Price, Box<dyn Error>>;
+    fn get_prices(
+        &self,
+        item_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Price>, Box<dyn Error>>;
+    fn update_price_conversion(&self, price: &Price) -> Result<(), Box<dyn Error>>;
     fn get_lowest_price_by_item_id(&self, item_id: i64) -> Result<Option<Price>, Box<dyn Error>>;
     fn get_lowest_price_before_price(&self, price: Price) -> Result<Option<Price>, Box<dyn Error>>;
     fn matches_condition(&self, price: Price, condition: Condition) -> bool;
@@ -98,34 +327,115 @@ impl std::error::Error for NoPriceFoundError {}
 
 /// Prices is the implementation of the Prices trait
 impl Prices for Database {
-    fn add_price(&self, price: &Price) -> Result<(), Box<dyn Error>> {
+    /// add the price to the database and return the ID of the inserted row
+    fn add_price(&self, price: &Price) -> Result<i64, Box<dyn Error>> {
         self.conn.execute(
-            "INSERT OR IGNORE INTO prices(
+            "INSERT INTO prices(
                     item_id, price, currency, converted_price, converted_currency, taxes,
-                    shipping, url, module, condition, tstamp
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    shipping, url, module, condition, tstamp, fees, duty, clearance_fee,
+                    item_grade, box_grade, availability, release_month, buyout_price, auction_end
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                    ?17, ?18, ?19, ?20)",
             params![
                 price.item_id,
-                format!("{:.2}", price.price),
-                price.currency,
-                format!("{:.2}", price.converted_price),
-                price.converted_currency,
+                price.price.amount,
+                price.price.currency,
+                price.converted_price.amount,
+                price.converted_price.currency,
                 price.taxes,
-                price.shipping,
+                price.shipping.amount,
                 price.url,
                 price.module,
                 price.condition,
-                price.timestamp
+                price.timestamp,
+                price.fees.amount,
+                price.duty.amount,
+                price.clearance_fee.amount,
+                price.item_grade,
+                price.box_grade,
+                price.availability,
+                price.release_month,
+                price
+                    .buyout_price
+                    .as_ref()
+                    .map(|buyout_price| buyout_price.amount),
+                price.auction_end
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// update the converted price, currency and costs of an already recorded price
+    fn update_price_conversion(&self, price: &Price) -> Result<(), Box<dyn Error>> {
+        self.conn.execute(
+            "UPDATE prices SET converted_price = ?1, converted_currency = ?2, shipping = ?3,
+                fees = ?4, duty = ?5, clearance_fee = ?6
+            WHERE id = ?7",
+            params![
+                price.converted_price.amount,
+                price.converted_price.currency,
+                price.shipping.amount,
+                price.fees.amount,
+                price.duty.amount,
+                price.clearance_fee.amount,
+                price.id
             ],
         )?;
 
         Ok(())
     }
 
+    /// retrieve a single price based on its ID
+    fn get_price(&self, id: i64) -> Result<Price, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, item_id, price, currency, converted_price, converted_currency, taxes,
+                    shipping, url, module, condition, tstamp, fees, duty, clearance_fee,
+                    item_grade, box_grade, availability, release_month, buyout_price, auction_end
+            FROM prices
+            WHERE id = ?1",
+        )?;
+
+        let mut price_iter = stmt.query_map(params![id], Price::from_row)?;
+
+        match price_iter.next() {
+            Some(price) => Ok(price?),
+            None => Err(Box::from(NoPriceFoundError {})),
+        }
+    }
+
+    /// retrieve the recorded prices, optionally limited to an item and a start date
+    /// the most recent prices are returned first
+    fn get_prices(
+        &self,
+        item_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Price>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, item_id, price, currency, converted_price, converted_currency, taxes,
+                    shipping, url, module, condition, tstamp, fees, duty, clearance_fee,
+                    item_grade, box_grade, availability, release_month, buyout_price, auction_end
+            FROM prices
+            WHERE (?1 IS NULL OR item_id = ?1)
+                AND (?2 IS NULL OR tstamp >= ?2)
+            ORDER BY tstamp DESC, item_id, converted_price",
+        )?;
+
+        let price_iter = stmt.query_map(params![item_id, since], Price::from_row)?;
+
+        let mut prices = vec![];
+        for price in price_iter {
+            prices.push(price?);
+        }
+
+        Ok(prices)
+    }
+
     fn get_lowest_price_by_item_id(&self, item_id: i64) -> Result<Option<Price>, Box<dyn Error>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, item_id, price, currency, converted_price, converted_currency, taxes,
-                    shipping, url, module, condition, tstamp
+                    shipping, url, module, condition, tstamp, fees, duty, clearance_fee,
+                    item_grade, box_grade, availability, release_month, buyout_price, auction_end
             FROM prices
             WHERE item_id = ?1
             GROUP BY item_id, tstamp
@@ -133,22 +443,7 @@ impl Prices for Database {
             LIMIT 1",
         )?;
 
-        let mut price_iter = stmt.query_map(params![item_id], |row| {
-            Ok(Price {
-                id: row.get(0)?,
-                item_id: row.get(1)?,
-                price: row.get(2)?,
-                currency: row.get(3)?,
-                converted_price: row.get(4)?,
-                converted_currency: row.get(5)?,
-                taxes: row.get(6)?,
-                shipping: row.get(7)?,
-                url: row.get(8)?,
-                module: row.get(9)?,
-                condition: row.get(10)?,
-                timestamp: row.get(11)?,
-            })
-        })?;
+        let mut price_iter = stmt.query_map(params![item_id], Price::from_row)?;
 
         match price_iter.next() {
             Some(price) => Ok(Some(price.unwrap())),
@@ -159,7 +454,8 @@ impl Prices for Database {
     fn get_lowest_price_before_price(&self, price: Price) -> Result<Option<Price>, Box<dyn Error>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, item_id, price, currency, converted_price, converted_currency, taxes,
-                    shipping, url, module, condition, tstamp
+                    shipping, url, module, condition, tstamp, fees, duty, clearance_fee,
+                    item_grade, box_grade, availability, release_month, buyout_price, auction_end
             FROM prices
             WHERE item_id = ?1
                 AND tstamp < ?2
@@ -168,22 +464,8 @@ impl Prices for Database {
             LIMIT 1",
         )?;
 
-        let mut price_iter = stmt.query_map(params![price.item_id, price.timestamp], |row| {
-            Ok(Price {
-                id: row.get(0)?,
-                item_id: row.get(1)?,
-                price: row.get(2)?,
-                currency: row.get(3)?,
-                converted_price: row.get(4)?,
-                converted_currency: row.get(5)?,
-                taxes: row.get(6)?,
-                shipping: row.get(7)?,
-                url: row.get(8)?,
-                module: row.get(9)?,
-                condition: row.get(10)?,
-                timestamp: row.get(11)?,
-            })
-        })?;
+        let mut price_iter =
+            stmt.query_map(params![price.item_id, price.timestamp], Price::from_row)?;
 
         match price_iter.next() {
             Some(price) => Ok(Some(price.unwrap())),
@@ -192,7 +474,8 @@ impl Prices for Database {
     }
 
     /// check if the passed price matches the passed condition and should notify the user
-    /// about the price
+    /// about the price, the item condition, the grades, the availability and the auction of the price
+    /// have to meet the requirements of the condition
     ///
     /// depending on the condition type the check changes
     /// ConditionType::BelowPrice -> below static value of the condition
@@ -201,16 +484,31 @@ impl Prices for Database {
     /// ConditionType::LowestPrice -> retrieve the lowest item so far and check if the current item is x amount below the value
     /// ConditionType::BelowPrice -> below static value of the condition
     /// ConditionType::PriceDrop -> retrieve the lowest last item recorded before the current item and check if the price dropped by x percentage compared to it
+    /// ConditionType::NewListing, ConditionType::BackInStock -> never match prices, they are checked on the listing events
     fn matches_condition(&self, price: Price, condition: Condition) -> bool {
+        // prices of other item conditions, below the minimum grades or excluded pre-orders never match
+        if !condition.matches_price(&price) {
+            return false;
+        }
+
+        // auctions are compared by their buyout price for buyout conditions
+        let price = match condition.get_compared_price(&price) {
+            Some(price) => price,
+            None => return false,
+        };
+
+        // the condition values are in the currency of the converted prices
+        let value = Money::from_major(condition.value, price.converted_price.currency.clone());
+
         match condition.condition_type {
-            ConditionType::BelowPrice => price.converted_price < condition.value,
-            ConditionType::BelowPriceTaxed => price.get_converted_taxed() < condition.value,
-            ConditionType::BelowPriceFull => price.get_converted_total() < condition.value,
+            ConditionType::BelowPrice => price.converted_price < value,
+            ConditionType::BelowPriceTaxed => price.get_converted_taxed() < value,
+            ConditionType::BelowPriceFull => price.get_converted_total() < value,
             ConditionType::LowestPrice => {
                 if let Ok(price_option) = self.get_lowest_price_by_item_id(price.item_id) {
                     match price_option {
                         Some(lowest_price) => {
-                            price.converted_price + condition.value < lowest_price.converted_price
+                            price.converted_price + value < lowest_price.converted_price
                         }
                         None => true,
                     }
@@ -222,7 +520,10 @@ impl Prices for Database {
                 if let Ok(price_option) = self.get_lowest_price_before_price(price.clone()) {
                     match price_option {
                         Some(previous_price) => {
-                            previous_price.converted_price * (1.0 - condition.value)
+                            // the value of price drop conditions is a percentage and no amount
+                            previous_price
+                                .converted_price
+                                .multiply(1.0 - condition.value)
                                 > price.converted_price
                         }
                         None => false,
@@ -231,6 +532,269 @@ impl Prices for Database {
                     false
                 }
             }
+            ConditionType::NewListing | ConditionType::BackInStock => false,
         }
     }
 }
+
+#[test]
+fn test_price_costs() {
+    let item = Item {
+        id: 1,
+        jan: 4_580_416_940_283,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+
+    let mut price = Price::new(
+        item,
+        Money::new(12_000, SupportedCurrency::JPY),
+        "".to_string(),
+        "amazon.co.jp".to_string(),
+        ItemConditions::New,
+    );
+    price.set_conversion(
+        Money::new(10_000, SupportedCurrency::EUR),
+        &ListingCosts {
+            fixed_fee: 5.0,
+            service_fee: 0.05,
+            payment_surcharge: 0.03,
+            shipping: 20.0,
+            taxes: 0.19,
+            import: None,
+        },
+    );
+
+    assert_eq!(price.shipping, Money::new(2_000, SupportedCurrency::EUR));
+    // 5% service fee and 5€ fixed fee plus 3% surcharge on 130€
+    assert_eq!(price.fees, Money::new(1_390, SupportedCurrency::EUR));
+    assert_eq!(
+        price.get_converted_taxed(),
+        Money::new(13_554, SupportedCurrency::EUR)
+    );
+    assert_eq!(
+        price.get_converted_total(),
+        Money::new(15_934, SupportedCurrency::EUR)
+    );
+}
+
+#[test]
+fn test_price_import_costs() {
+    use crate::configuration::ImportRules;
+
+    let item = Item {
+        id: 1,
+        jan: 4_580_416_940_283,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+    let costs = ListingCosts {
+        shipping: 20.0,
+        taxes: 0.19,
+        import: Some(ImportRules {
+            vat: 0.19,
+            duty: 0.05,
+            clearance_fee: 6.0,
+            vat_threshold: 22.0,
+            duty_threshold: 150.0,
+        }),
+        ..ListingCosts::default()
+    };
+    let get_price = |value: i64| {
+        let mut price = Price::new(
+            item.clone(),
+            Money::new(value * 120, SupportedCurrency::JPY),
+            "".to_string(),
+            "amiami.com".to_string(),
+            ItemConditions::New,
+        );
+        price.set_conversion(Money::new(value, SupportedCurrency::EUR), &costs);
+        price
+    };
+
+    // below the VAT threshold no import costs are due
+    let price = get_price(2_000);
+    assert_eq!(price.get_converted_taxes().amount, 0);
+    assert_eq!(price.clearance_fee.amount, 0);
+    assert_eq!(price.get_converted_total().amount, 4_000);
+
+    // VAT on the value including shipping, no duty below the duty threshold
+    let price = get_price(10_000);
+    assert_eq!(price.duty.amount, 0);
+    assert_eq!(price.get_converted_taxes().amount, 2_280);
+    assert_eq!(price.get_converted_total().amount, 14_880);
+
+    // duty above the duty threshold, the VAT also applies to the duty
+    let price = get_price(20_000);
+    assert_eq!(price.duty.amount, 1_100);
+    assert_eq!(price.get_converted_taxes().amount, 4_389);
+    assert_eq!(price.get_converted_total().amount, 28_089);
+}
+
+#[test]
+fn test_price_grades() {
+    let item = Item {
+        id: 1,
+        jan: 4_580_416_940_283,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+    let new_price = Price::new(
+        item.clone(),
+        Money::new(12_000, SupportedCurrency::JPY),
+        "".to_string(),
+        "amiami.com".to_string(),
+        ItemConditions::New,
+    );
+    let used_price = Price::new(
+        item.clone(),
+        Money::new(9_000, SupportedCurrency::JPY),
+        "".to_string(),
+        "amiami.com".to_string(),
+        ItemConditions::Used,
+    )
+    .with_grades(Some(Grade::A), Some(Grade::C));
+    let ungraded_price = Price::new(
+        item,
+        Money::new(8_000, SupportedCurrency::JPY),
+        "".to_string(),
+        "amiami.com".to_string(),
+        ItemConditions::Used,
+    );
+
+    // used, but item grade A or better
+    let mut condition = Condition::new(ConditionType::BelowPrice, ItemConditions::Used, 0.0, 1);
+    condition.min_item_grade = Some(Grade::A);
+    assert!(!condition.matches_price(&new_price));
+    assert!(condition.matches_price(&used_price));
+    assert!(!condition.matches_price(&ungraded_price));
+
+    // new items without a grading are considered mint
+    let mut condition = Condition::new(ConditionType::BelowPrice, ItemConditions::All, 0.0, 1);
+    condition.min_box_grade = Some(Grade::B);
+    assert!(condition.matches_price(&new_price));
+    assert!(!condition.matches_price(&used_price));
+
+    condition.min_box_grade = Some(Grade::C);
+    assert!(condition.matches_price(&used_price));
+}
+
+#[test]
+fn test_price_pre_orders() {
+    use crate::database::conditions::PreOrders;
+    use crate::database::items::Items;
+
+    let db = Database::open(":memory:").unwrap();
+    let item = db.add_item(4_580_416_940_283).unwrap();
+    let pre_order = Price::new(
+        item.clone(),
+        Money::new(12_000, SupportedCurrency::JPY),
+        "".to_string(),
+        "amiami.com".to_string(),
+        ItemConditions::New,
+    )
+    .with_availability(Availability::PreOrder, NaiveDate::from_ymd_opt(2024, 5, 1));
+    let in_stock = Price::new(
+        item.clone(),
+        Money::new(14_000, SupportedCurrency::JPY),
+        "".to_string(),
+        "amiami.com".to_string(),
+        ItemConditions::New,
+    );
+
+    // the availability and the release month are recorded with the price
+    let id = db.add_price(&pre_order).unwrap();
+    let price = db.get_price(id).unwrap();
+    assert_eq!(price.availability, Availability::PreOrder);
+    assert_eq!(price.get_availability_text(), "pre_order (2024-05)");
+    assert_eq!(in_stock.get_availability_text(), "in_stock");
+
+    let mut condition =
+        Condition::new(ConditionType::BelowPrice, ItemConditions::All, 0.0, item.id);
+    assert!(condition.matches_price(&pre_order));
+    assert!(condition.matches_price(&in_stock));
+
+    condition.pre_orders = PreOrders::Exclude;
+    assert!(!condition.matches_price(&pre_order));
+    assert!(condition.matches_price(&in_stock));
+
+    condition.pre_orders = PreOrders::Only;
+    assert!(condition.matches_price(&pre_order));
+    assert!(!condition.matches_price(&in_stock));
+}
+
+#[test]
+fn test_price_auctions() {
+    use chrono::Duration;
+
+    let db = Database::open(":memory:").unwrap();
+    let item = Item {
+        id: 1,
+        jan: 4_580_416_940_283,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+    let get_price = |buyout_price: Option<i64>, hours_left: Option<i64>| {
+        let mut price = Price::new(
+            item.clone(),
+            Money::new(8_000, SupportedCurrency::JPY),
+            "".to_string(),
+            "auctions.yahoo.co.jp".to_string(),
+            ItemConditions::Used,
+        );
+        if let Some(hours_left) = hours_left {
+            let auction_end = price.timestamp + Duration::hours(hours_left);
+            price = price.with_auction(
+                buyout_price.map(|buyout_price| Money::new(buyout_price, SupportedCurrency::JPY)),
+                auction_end,
+            );
+        }
+        price.set_conversion(
+            Money::new(6_000, SupportedCurrency::EUR),
+            &ListingCosts {
+                shipping: 10.0,
+                ..ListingCosts::default()
+            },
+        );
+        price
+    };
+
+    // the buyout price is converted with the exchange rate of the bid and includes the same costs
+    let auction = get_price(Some(12_000), Some(12));
+    let buyout = auction.get_buyout_price().unwrap();
+    assert_eq!(buyout.price, Money::new(12_000, SupportedCurrency::JPY));
+    assert_eq!(
+        buyout.get_converted_total(),
+        Money::new(10_000, SupportedCurrency::EUR)
+    );
+
+    let condition = |value: f64, buyout_only: bool, ending_within: Option<u32>| {
+        let mut condition =
+            Condition::new(ConditionType::BelowPriceFull, ItemConditions::All, value, 1);
+        condition.buyout_only = buyout_only;
+        condition.ending_within = ending_within;
+        condition
+    };
+    assert!(db.matches_condition(auction.clone(), condition(95.0, false, None)));
+    assert!(!db.matches_condition(auction.clone(), condition(95.0, true, None)));
+    assert!(db.matches_condition(auction.clone(), condition(105.0, true, None)));
+    // auctions without buyout price never match buyout conditions, other prices are compared as usual
+    assert!(!db.matches_condition(get_price(None, Some(12)), condition(105.0, true, None)));
+    assert!(db.matches_condition(get_price(None, None), condition(105.0, true, None)));
+
+    assert!(db.matches_condition(auction.clone(), condition(95.0, false, Some(24))));
+    assert!(!db.matches_condition(get_price(None, Some(36)), condition(95.0, false, Some(24))));
+    assert!(!db.matches_condition(get_price(None, None), condition(95.0, false, Some(24))));
+}
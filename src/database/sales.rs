@@ -0,0 +1,185 @@
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Row};
+
+use crate::database::listings::Listing;
+use crate::database::prices::Price;
+use crate::database::Database;
+
+/// Sale is a sold listing of a module with the price the item was actually sold for
+#[derive(Clone, Debug)]
+pub(crate) struct Sale {
+    pub(crate) id: Option<i64>,
+    /// the realized price of the sale, the timestamp of the price is the time the sale was recorded
+    pub(crate) price: Price,
+    pub(crate) seller: String,
+    pub(crate) sold_at: DateTime<Utc>,
+}
+
+impl Sale {
+    /// retrieve the sale of a listing which the module reported as sold,
+    /// the sale time falls back to the time the listing was seen if the module doesn't provide it
+    pub fn from_listing(listing: Listing) -> Self {
+        Sale {
+            id: None,
+            sold_at: listing.sold_out_at.unwrap_or(listing.price.timestamp),
+            price: listing.price,
+            seller: listing.seller,
+        }
+    }
+
+    /// retrieve the sale from a selected row with the price columns in the order
+    /// of the prices table followed by the sale specific columns
+    fn from_row(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        let mut price = Price::from_row(row)?;
+        let id = price.id.take();
+
+        Ok(Sale {
+            id,
+            price,
+            seller: row.get(21)?,
+            sold_at: row.get(22)?,
+        })
+    }
+}
+
+/// Sales implements all related functionality for sales to interact with the database
+pub(crate) trait Sales {
+    fn record_sale(&self, sale: &Sale) -> Result<bool, Box<dyn Error>>;
+    fn get_sales(
+        &self,
+        item_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Sale>, Box<dyn Error>>;
+}
+
+/// Sales is the implementation of the Sales trait
+impl Sales for Database {
+    /// add the sale unless it is already known and return if the sale was newly recorded,
+    /// sold listings stay visible on the marketplaces so the same sale is found on every check
+    fn record_sale(&self, sale: &Sale) -> Result<bool, Box<dyn Error>> {
+        let price = &sale.price;
+        let inserted = self.conn.execute(
+            "INSERT INTO sales(
+                    item_id, price, currency, converted_price, converted_currency, taxes,
+                    shipping, url, module, condition, recorded_at, fees, duty, clearance_fee,
+                    item_grade, box_grade, availability, release_month, buyout_price, auction_end,
+                    seller, sold_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                    ?17, ?18, ?19, ?20, ?21, ?22)
+                ON CONFLICT (item_id, module, url) DO NOTHING",
+            params![
+                price.item_id,
+                price.price.amount,
+                price.price.currency,
+                price.converted_price.amount,
+                price.converted_price.currency,
+                price.taxes,
+                price.shipping.amount,
+                price.url,
+                price.module,
+                price.condition,
+                price.timestamp,
+                price.fees.amount,
+                price.duty.amount,
+                price.clearance_fee.amount,
+                price.item_grade,
+                price.box_grade,
+                price.availability,
+                price.release_month,
+                price
+                    .buyout_price
+                    .as_ref()
+                    .map(|buyout_price| buyout_price.amount),
+                price.auction_end,
+                sale.seller,
+                sale.sold_at
+            ],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    /// retrieve the recorded sales, optionally limited to an item
+    /// and to sales since the passed date, the latest sales are returned first
+    fn get_sales(
+        &self,
+        item_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Sale>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, item_id, price, currency, converted_price, converted_currency, taxes,
+                    shipping, url, module, condition, recorded_at, fees, duty, clearance_fee,
+                    item_grade, box_grade, availability, release_month, buyout_price, auction_end,
+                    seller, sold_at
+            FROM sales
+            WHERE (?1 IS NULL OR item_id = ?1)
+                AND (?2 IS NULL OR sold_at >= ?2)
+            ORDER BY item_id, sold_at DESC",
+        )?;
+
+        let sale_iter = stmt.query_map(params![item_id, since], Sale::from_row)?;
+
+        let mut sales = vec![];
+        for sale in sale_iter {
+            sales.push(sale?);
+        }
+
+        Ok(sales)
+    }
+}
+
+#[test]
+fn test_record_sales() {
+    use chrono::Duration;
+
+    use crate::currency::money::Money;
+    use crate::currency::SupportedCurrency;
+    use crate::database::items::{Grade, ItemConditions, Items};
+
+    let db = Database::open(":memory:").unwrap();
+    let item = db.add_item(4_580_416_940_283).unwrap();
+
+    let sold_at = Utc::now() - Duration::days(3);
+    let get_listing = |value: i64, url: &str| {
+        let mut listing = Listing::new(
+            Price::new(
+                item.clone(),
+                Money::new(value, SupportedCurrency::JPY),
+                url.to_string(),
+                "jp.mercari.com".to_string(),
+                ItemConditions::Used,
+            )
+            .with_grades(Some(Grade::B), None),
+            "seller".to_string(),
+            "".to_string(),
+        );
+        listing.in_stock = false;
+        listing.sold = true;
+        listing.sold_out_at = Some(sold_at);
+        listing
+    };
+
+    // the same sale found again on the next check is only recorded once
+    let sale = Sale::from_listing(get_listing(6500, "https://jp.mercari.com/item/m1"));
+    assert_eq!(sale.sold_at, sold_at);
+    assert!(db.record_sale(&sale).unwrap());
+    assert!(!db.record_sale(&sale).unwrap());
+
+    // sales without a known sale time were sold when they were seen
+    let mut listing = get_listing(8000, "https://jp.mercari.com/item/m2");
+    listing.sold_out_at = None;
+    let sale = Sale::from_listing(listing);
+    assert_eq!(sale.sold_at, sale.price.timestamp);
+    assert!(db.record_sale(&sale).unwrap());
+
+    let sales = db.get_sales(Some(item.id), None).unwrap();
+    assert_eq!(sales.len(), 2);
+    assert_eq!(sales[0].price.price.amount, 8000);
+    assert_eq!(sales[1].price.item_grade, Some(Grade::B));
+    assert_eq!(sales[1].seller, "seller");
+
+    let since = Utc::now() - Duration::days(1);
+    assert_eq!(db.get_sales(None, Some(since)).unwrap().len(), 1);
+}
@@ -0,0 +1,138 @@
+use std::error::Error;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::http::{RequestBuilder, Response};
+
+/// Interaction is a single recorded request and the response to it,
+/// the response body is stored in a separate file next to the cassette
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct Interaction {
+    pub(crate) method: String,
+    pub(crate) url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) body: Option<String>,
+    pub(crate) status: u16,
+    /// final URL of the response after following all redirects
+    pub(crate) response_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_type: Option<String>,
+    /// file containing the response body, relative to the cassette directory
+    pub(crate) file: String,
+}
+
+/// Cassette contains all recorded interactions of a fixture
+#[derive(Debug)]
+pub(crate) struct Cassette {
+    path: PathBuf,
+    interactions: Vec<Interaction>,
+}
+
+/// custom error if no recorded interaction matches the request
+#[derive(Debug)]
+pub(crate) struct MissingInteractionError {
+    method: String,
+    url: String,
+    path: PathBuf,
+}
+
+impl std::fmt::Display for MissingInteractionError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "no recorded response for {} {} in {}",
+            self.method,
+            self.url,
+            self.path.display()
+        )
+    }
+}
+
+impl Error for MissingInteractionError {}
+
+impl Cassette {
+    /// retrieve the directory of the passed fixture
+    fn get_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join("http")
+            .join(name)
+    }
+
+    /// create new empty cassette for the passed fixture to record the interactions into
+    pub fn new(name: &str) -> Self {
+        Cassette {
+            path: Cassette::get_path(name),
+            interactions: vec![],
+        }
+    }
+
+    /// load the recorded interactions of the passed fixture
+    pub fn load(name: &str) -> Result<Self, Box<dyn Error>> {
+        let path = Cassette::get_path(name);
+        let interactions = serde_json::from_str(&read_to_string(path.join("cassette.json"))?)?;
+
+        Ok(Cassette { path, interactions })
+    }
+
+    /// answer the request with the first recorded interaction matching the method, URL and body
+    pub fn replay(&self, request: &RequestBuilder<'_>) -> Result<Response, Box<dyn Error>> {
+        let interaction = self
+            .interactions
+            .iter()
+            .find(|interaction| {
+                interaction.method == request.method.as_str()
+                    && interaction.url == request.url
+                    && interaction.body == request.body
+            })
+            .ok_or_else(|| MissingInteractionError {
+                method: request.method.to_string(),
+                url: request.url.clone(),
+                path: self.path.clone(),
+            })?;
+
+        Ok(Response {
+            status: interaction.status,
+            url: interaction.response_url.clone(),
+            content_type: interaction.content_type.clone(),
+            body: read_to_string(self.path.join(&interaction.file))?,
+        })
+    }
+
+    /// add the request and its response to the cassette and save the cassette
+    pub fn record(
+        &mut self,
+        request: &RequestBuilder<'_>,
+        response: &Response,
+    ) -> Result<(), Box<dyn Error>> {
+        let extension = match &response.content_type {
+            Some(content_type) if content_type.contains("json") => "json",
+            Some(content_type) if content_type.contains("xml") => "xml",
+            Some(content_type) if content_type.contains("html") => "html",
+            _ => "txt",
+        };
+        let file = format!("{:03}.{}", self.interactions.len() + 1, extension);
+
+        create_dir_all(&self.path)?;
+        write(self.path.join(&file), &response.body)?;
+
+        self.interactions.push(Interaction {
+            method: request.method.to_string(),
+            url: request.url.clone(),
+            body: request.body.clone(),
+            status: response.status,
+            response_url: response.url.clone(),
+            content_type: response.content_type.clone(),
+            file,
+        });
+
+        write(
+            self.path.join("cassette.json"),
+            serde_json::to_string_pretty(&self.interactions)?,
+        )?;
+
+        Ok(())
+    }
+}
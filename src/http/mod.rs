@@ -1,57 +1,90 @@
 use std::error::Error;
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use reqwest::header::{HeaderMap, ACCEPT_LANGUAGE, USER_AGENT};
-use yaml_rust::Yaml;
-
-use crate::configuration::Configuration;
-
-/// small extracted function to check if the checked key exists and is not empty
-fn is_config_proxy_key_set(config: &Yaml, key: String) -> bool {
-    !config["connection"]["proxy"][key.as_str()].is_badvalue()
-        && config["connection"]["proxy"][key.as_str()]
-            .as_str()
-            .is_some()
-        && !config["connection"]["proxy"][key.as_str()]
-            .as_str()
-            .unwrap()
-            .is_empty()
+use reqwest::Method;
+
+use crate::configuration::ConnectionConfig;
+#[cfg(test)]
+use crate::http::fixtures::Cassette;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+/// Client is the HTTP client used by all modules, in tests it can replay the responses
+/// from checked in fixtures or record new fixtures from the live sites
+/// (set the RECORD_FIXTURES environment variable to record the fixtures again)
+#[derive(Clone)]
+pub(crate) struct Client {
+    mode: Mode,
+}
+
+/// the mode of the client on how the requests are handled
+#[derive(Clone)]
+enum Mode {
+    /// send all requests to the live sites
+    Live(reqwest::blocking::Client),
+    /// send all requests to the live sites and record the responses into the cassette
+    #[cfg(test)]
+    Record(reqwest::blocking::Client, Arc<Mutex<Cassette>>),
+    /// answer all requests with the recorded responses from the cassette
+    #[cfg(test)]
+    Replay(Arc<Cassette>),
+}
+
+/// RequestBuilder collects the request details until the request is sent
+pub(crate) struct RequestBuilder<'a> {
+    client: &'a Client,
+    pub(crate) method: Method,
+    pub(crate) url: String,
+    headers: Vec<(&'static str, String)>,
+    pub(crate) body: Option<String>,
+}
+
+/// Response contains the already read response of a sent or replayed request,
+/// the status and content type are only required for recording the fixtures
+pub(crate) struct Response {
+    #[cfg(test)]
+    pub(crate) status: u16,
+    pub(crate) url: String,
+    #[cfg(test)]
+    pub(crate) content_type: Option<String>,
+    pub(crate) body: String,
+}
+
+/// small extracted function to check if the optional value is set and not empty
+fn is_config_value_set(value: &Option<String>) -> bool {
+    value.as_ref().map_or(false, |value| !value.is_empty())
 }
 
 /// retrieve the reqwest client based on the connection settings from the configuration
-pub fn get_client() -> Result<reqwest::blocking::Client, Box<dyn Error>> {
-    let config = Configuration::get_configuration()?;
+pub fn get_client(
+    connection: &ConnectionConfig,
+) -> Result<reqwest::blocking::Client, Box<dyn Error>> {
     let mut builder = reqwest::blocking::Client::builder();
 
-    if is_config_proxy_key_set(&config, "host".to_string()) {
+    if is_config_value_set(&connection.proxy.host) {
         // proxy host is required
-        let mut proxy_host = config["connection"]["proxy"]["host"]
-            .as_str()
-            .unwrap()
-            .to_string();
+        let mut proxy_host = connection.proxy.host.clone().unwrap();
 
         // proxy port is optional
-        if !config["connection"]["proxy"]["port"].is_badvalue()
-            && config["connection"]["proxy"]["port"].as_i64().is_some()
-        {
-            proxy_host = format!(
-                "{}:{:?}",
-                &proxy_host,
-                config["connection"]["proxy"]["port"].as_i64().unwrap()
-            );
+        if let Some(port) = connection.proxy.port {
+            proxy_host = format!("{}:{:?}", &proxy_host, port);
         }
 
         // create the proxy
         let mut proxy = reqwest::Proxy::all(proxy_host.as_str())?;
 
         // if a username is set we also require a password
-        if is_config_proxy_key_set(&config, "username".to_string())
-            && is_config_proxy_key_set(&config, "password".to_string())
+        if is_config_value_set(&connection.proxy.username)
+            && is_config_value_set(&connection.proxy.password)
         {
             // update the proxy object to include basic auth headers in the requests
             proxy = proxy.basic_auth(
-                config["connection"]["proxy"]["username"].as_str().unwrap(),
-                config["connection"]["proxy"]["password"].as_str().unwrap(),
+                connection.proxy.username.as_ref().unwrap(),
+                connection.proxy.password.as_ref().unwrap(),
             )
         }
 
@@ -65,15 +98,8 @@ pub fn get_client() -> Result<reqwest::blocking::Client, Box<dyn Error>> {
     header_map.insert(ACCEPT_LANGUAGE, "en-US,en;q=0.5".parse().unwrap());
 
     // set optional user agent
-    if !config["connection"]["user-agent"].is_badvalue() {
-        header_map.insert(
-            USER_AGENT,
-            config["connection"]["user-agent"]
-                .as_str()
-                .unwrap()
-                .parse()
-                .unwrap(),
-        );
+    if let Some(user_agent) = &connection.user_agent {
+        header_map.insert(USER_AGENT, user_agent.parse()?);
     }
 
     builder = builder.default_headers(header_map);
@@ -84,3 +110,122 @@ pub fn get_client() -> Result<reqwest::blocking::Client, Box<dyn Error>> {
         .timeout(Duration::from_secs(30))
         .build()?)
 }
+
+impl Client {
+    /// create new client sending the requests to the live sites
+    pub fn new(connection: &ConnectionConfig) -> Result<Self, Box<dyn Error>> {
+        Ok(Client {
+            mode: Mode::Live(get_client(connection)?),
+        })
+    }
+
+    /// create new client replaying the passed fixture or recording it
+    /// if the RECORD_FIXTURES environment variable is set
+    #[cfg(test)]
+    pub fn from_fixture(name: &str) -> Self {
+        if std::env::var_os("RECORD_FIXTURES").is_some() {
+            return Client {
+                mode: Mode::Record(
+                    get_client(&ConnectionConfig::default()).unwrap(),
+                    Arc::new(Mutex::new(Cassette::new(name))),
+                ),
+            };
+        }
+
+        match Cassette::load(name) {
+            Ok(cassette) => Client {
+                mode: Mode::Replay(Arc::new(cassette)),
+            },
+            Err(err) => panic!("couldn't load fixture {:?} (err: {:?})", name, err),
+        }
+    }
+
+    /// start building a GET request to the passed URL
+    pub fn get(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::GET, url)
+    }
+
+    /// start building a POST request to the passed URL
+    pub fn post(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::POST, url)
+    }
+
+    /// start building a request with the passed method to the passed URL
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder<'_> {
+        RequestBuilder {
+            client: self,
+            method,
+            url: url.to_string(),
+            headers: vec![],
+            body: None,
+        }
+    }
+}
+
+impl RequestBuilder<'_> {
+    /// add a header to the request
+    pub fn header(mut self, key: &'static str, value: &str) -> Self {
+        self.headers.push((key, value.to_string()));
+        self
+    }
+
+    /// set the body of the request
+    pub fn body(mut self, body: String) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// send the request or replay the recorded response
+    pub fn send(self) -> Result<Response, Box<dyn Error>> {
+        match &self.client.mode {
+            Mode::Live(client) => self.send_live(client),
+            #[cfg(test)]
+            Mode::Record(client, cassette) => {
+                let res = self.send_live(client)?;
+                cassette.lock().unwrap().record(&self, &res)?;
+                Ok(res)
+            }
+            #[cfg(test)]
+            Mode::Replay(cassette) => cassette.replay(&self),
+        }
+    }
+
+    /// send the request to the live site and read the complete response
+    fn send_live(&self, client: &reqwest::blocking::Client) -> Result<Response, Box<dyn Error>> {
+        let mut req = client.request(self.method.clone(), self.url.as_str());
+        for (key, value) in self.headers.iter() {
+            req = req.header(*key, value.as_str());
+        }
+
+        if let Some(body) = &self.body {
+            req = req.body(body.clone());
+        }
+
+        let res = req.send()?;
+
+        Ok(Response {
+            #[cfg(test)]
+            status: res.status().as_u16(),
+            url: res.url().as_str().to_string(),
+            #[cfg(test)]
+            content_type: res
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+            body: res.text()?,
+        })
+    }
+}
+
+impl Response {
+    /// retrieve the final URL of the response after following all redirects
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    /// retrieve the body of the response
+    pub fn text(self) -> Result<String, Box<dyn Error>> {
+        Ok(self.body)
+    }
+}
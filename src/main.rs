@@ -2,76 +2,145 @@ extern crate chrono;
 extern crate env_logger;
 #[macro_use]
 extern crate log;
-extern crate yaml_rust;
 
 use std::borrow::BorrowMut;
+use std::collections::HashMap;
 use std::error::Error;
 use std::io::Write;
+use std::path::PathBuf;
 use std::thread::JoinHandle;
-use std::{process, thread};
+use std::{fs, process, thread, time};
 
-use chrono::{Local, Utc};
+use chrono::{Local, NaiveDate, Utc};
 use clap::Parser;
 use env_logger::Builder;
 use log::LevelFilter;
-use yaml_rust::Yaml;
+use serde_json::Value;
 
 use crate::cli::*;
-use crate::configuration::Configuration;
+use crate::configuration::{expand_path, Config};
+use crate::currency::conversion::CurrencyConversion;
+use crate::currency::money::Money;
+use crate::currency::providers::ecb::Ecb;
+use crate::daemon::Scheduler;
 use crate::database::conditions::{Condition, Conditions};
+use crate::database::exchange_rates::ExchangeRates;
 use crate::database::items::{Item, ItemConditions, Items};
+use crate::database::listings::{Listing, ListingEvent, Listings};
+use crate::database::migrations::{get_latest_version, Migration, MIGRATIONS};
+use crate::database::notifications::{Notification, Notifications};
 use crate::database::prices::{Price, Prices};
+use crate::database::sales::Sales;
 use crate::database::Database;
+use crate::http::Client;
 use crate::modules::ModulePool;
 use crate::notifications::NotificationManager;
+use crate::output::Table;
 
 mod cli;
 mod conditions;
 mod configuration;
 mod currency;
+mod daemon;
 mod database;
 mod http;
 mod modules;
 mod notifications;
+mod output;
+
+/// NotificationHandle is the thread sending the notifications about a matched condition
+/// and the failed notification to record if the thread panics
+struct NotificationHandle {
+    handle: JoinHandle<Vec<Notification>>,
+    channels: Vec<&'static str>,
+    failure: Notification,
+}
+
+impl NotificationHandle {
+    /// wait for the notification thread and retrieve the sent notifications,
+    /// a panicked thread is recorded as failed notification over all dispatched channels
+    fn join(self) -> Vec<Notification> {
+        match self.handle.join() {
+            Ok(notifications) => notifications,
+            Err(err) => {
+                let error = match err.downcast_ref::<&str>() {
+                    Some(message) => message.to_string(),
+                    None => match err.downcast_ref::<String>() {
+                        Some(message) => message.clone(),
+                        None => "unknown panic".to_string(),
+                    },
+                };
+                warn!("notification thread panicked (err: {:?})", error);
+
+                self.channels
+                    .iter()
+                    .map(|channel| Notification {
+                        channel: channel.to_string(),
+                        error: format!("notification thread panicked: {}", error),
+                        timestamp: Utc::now(),
+                        ..self.failure.clone()
+                    })
+                    .collect()
+            }
+        }
+    }
+}
 
 /// Main application for figure tracker
 struct FigureTracker {
     options: FigureTrackerOptions,
-    module_pool: ModulePool,
+    module_pool: Option<ModulePool>,
     db: Option<Database>,
-    config: Option<Yaml>,
+    config: Config,
     notifications: NotificationManager,
 }
 
 /// main implementation of the figure tracker
 impl FigureTracker {
-    /// initializing function parsing the CLI options
+    /// initializing function parsing the CLI options and loading the configuration
     pub fn new() -> Result<Self, Box<dyn Error>> {
+        let options = FigureTrackerOptions::parse();
+        FigureTracker::initialize_logger(&options);
+
+        let config = Config::load(options.config.as_str())?;
+        let db = FigureTracker::open_database(&options, &config);
+
         Ok(FigureTracker {
-            options: FigureTrackerOptions::parse(),
-            module_pool: ModulePool::new()?,
-            db: None,
-            config: None,
-            notifications: NotificationManager::new(),
+            module_pool: None,
+            db: Some(db),
+            notifications: NotificationManager::new(&config)?,
+            options,
+            config,
         })
     }
 
+    /// check if the passed command retrieves prices or item information from the modules,
+    /// other commands shouldn't depend on the network or the exchange rates
+    fn requires_module_pool(subcmd: &SubCommand) -> bool {
+        match subcmd {
+            SubCommand::Add(t) => matches!(t.subcmd, AddSubCommand::Item(_)),
+            SubCommand::Update(t) => match &t.subcmd {
+                UpdateSubCommand::Item(item) => item.update_info,
+                UpdateSubCommand::Prices(_) => true,
+                UpdateSubCommand::Rates(_) => false,
+            },
+            SubCommand::Run(_) => true,
+            _ => false,
+        }
+    }
+
     /// main entry point, here the CLI options are parsed
     pub fn execute(&mut self) {
-        self.initialize_logger();
-
-        match Configuration::get_configuration() {
-            Ok(config) => {
-                self.config = Some(config);
+        if FigureTracker::requires_module_pool(&self.options.subcmd) {
+            match ModulePool::new(&self.config, self.db.as_ref().unwrap()) {
+                Ok(module_pool) => self.module_pool = Some(module_pool),
+                Err(err) => {
+                    error!("couldn't initialize the modules (err: {:?})", err);
+                    return;
+                }
             }
-            Err(err) => error!(
-                "couldn't parse or create the configuration (err: {:?})",
-                err
-            ),
         }
 
-        self.open_database();
-
         match &self.options.subcmd {
             SubCommand::Add(t) => match &t.subcmd {
                 AddSubCommand::Item(item) => {
@@ -82,16 +151,81 @@ impl FigureTracker {
                 }
             },
             SubCommand::Update(t) => match &t.subcmd {
-                UpdateSubCommand::Item(_item) => unimplemented!(),
+                UpdateSubCommand::Item(item) => {
+                    self.update_item(item);
+                }
                 UpdateSubCommand::Prices(_t) => {
                     self.update_prices();
                 }
+                UpdateSubCommand::Rates(update_rates) => {
+                    self.update_rates(update_rates);
+                }
+            },
+            SubCommand::List(t) => match &t.subcmd {
+                ListSubCommand::Items(list_items) => {
+                    self.list_items(list_items);
+                }
+                ListSubCommand::Conditions(list_conditions) => {
+                    self.list_conditions(list_conditions);
+                }
+                ListSubCommand::Prices(list_prices) => {
+                    self.list_prices(list_prices);
+                }
+                ListSubCommand::Listings(list_listings) => {
+                    self.list_listings(list_listings);
+                }
+                ListSubCommand::Sales(list_sales) => {
+                    self.list_sales(list_sales);
+                }
+            },
+            SubCommand::Run(run) => {
+                let run = run.clone();
+                self.run_daemon(&run);
+            }
+            SubCommand::History(t) => match &t.subcmd {
+                HistorySubCommand::Notifications(history_notifications) => {
+                    self.history_notifications(history_notifications);
+                }
+            },
+            SubCommand::Remove(t) => match &t.subcmd {
+                RemoveSubCommand::Item(remove_item) => {
+                    self.remove_items(remove_item);
+                }
+                RemoveSubCommand::Condition(ids) => {
+                    self.remove_conditions(ids);
+                }
+            },
+            SubCommand::Disable(t) => match &t.subcmd {
+                ToggleSubCommand::Item(jans) => {
+                    self.set_items_disabled(jans, true);
+                }
+                ToggleSubCommand::Condition(ids) => {
+                    self.set_conditions_disabled(ids, true);
+                }
+            },
+            SubCommand::Enable(t) => match &t.subcmd {
+                ToggleSubCommand::Item(jans) => {
+                    self.set_items_disabled(jans, false);
+                }
+                ToggleSubCommand::Condition(ids) => {
+                    self.set_conditions_disabled(ids, false);
+                }
+            },
+            SubCommand::Prices(t) => match &t.subcmd {
+                PricesSubCommand::Reconvert(reconvert) => {
+                    self.reconvert_prices(reconvert);
+                }
+            },
+            SubCommand::Db(t) => match &t.subcmd {
+                DbSubCommand::Migrate(migrate) => {
+                    self.migrate_database(migrate);
+                }
             },
         }
     }
 
     /// initializes the logger across the project
-    pub fn initialize_logger(&self) {
+    pub fn initialize_logger(options: &FigureTrackerOptions) {
         // initialize our logger
         let mut logger = Builder::new();
         logger.format(|buf, record| {
@@ -105,7 +239,7 @@ impl FigureTracker {
         });
 
         // set the log level here
-        match self.options.verbose {
+        match options.verbose {
             0 => {
                 logger.filter(None, LevelFilter::Error);
             }
@@ -124,23 +258,44 @@ impl FigureTracker {
         logger.init();
 
         // small info about which config file was used
-        info!("value for config: {}", self.options.config);
+        info!("value for config: {}", options.config);
     }
 
     /// open or create the requested SQLite database file, exits if an error occurred
-    pub fn open_database(&mut self) {
-        if self.config.as_ref().unwrap()["database"]["path"].is_badvalue() {
-            error!("path to the database file is not defined");
-            process::exit(1)
-        }
+    pub fn open_database(options: &FigureTrackerOptions, config: &Config) -> Database {
+        // the CLI option is relative to the working directory, the configuration relative to its file
+        let path = match &options.database {
+            Some(database) => expand_path(database, None),
+            None => PathBuf::from(&config.database.path),
+        };
+        info!("using database: {}", path.display());
 
-        let db = database::Database::open("figure_tracker.db");
-        if let Err(db) = db {
-            error!("couldn't open database (err: {:?})", db);
-            process::exit(1)
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                if let Err(err) = fs::create_dir_all(parent) {
+                    error!(
+                        "couldn't create the database directory {} (err: {:?})",
+                        parent.display(),
+                        err
+                    );
+                    process::exit(1)
+                }
+            }
         }
 
-        self.db = Option::from(db.unwrap());
+        // the migrations are checked and applied manually with the db subcommand
+        let db = if !matches!(options.subcmd, SubCommand::Db(_)) {
+            database::Database::open(&path)
+        } else {
+            database::Database::connect(&path).map_err(Box::from)
+        };
+        match db {
+            Ok(db) => db,
+            Err(err) => {
+                error!("couldn't open database (err: {:?})", err);
+                process::exit(1)
+            }
+        }
     }
 
     /// adds the passed items to the database
@@ -161,7 +316,7 @@ impl FigureTracker {
         add_notification.items.iter().for_each(|jan| {
             match self.db.as_ref().unwrap().get_item(jan.clone()) {
                 Ok(item) => {
-                    match self.db.as_ref().unwrap().add_condition(Condition::new(
+                    let mut condition = Condition::new(
                         add_notification.condition_type,
                         match add_notification.condition {
                             Some(cond) => cond,
@@ -169,7 +324,14 @@ impl FigureTracker {
                         },
                         add_notification.value,
                         item.id,
-                    )) {
+                    );
+                    condition.min_item_grade = add_notification.min_item_grade;
+                    condition.min_box_grade = add_notification.min_box_grade;
+                    condition.pre_orders = add_notification.pre_orders;
+                    condition.buyout_only = add_notification.buyout_only;
+                    condition.ending_within = add_notification.ending_within;
+
+                    match self.db.as_ref().unwrap().add_condition(condition) {
                         Ok(_) => info!(
                             "successfully added condition for item: {:?}",
                             item.description
@@ -185,9 +347,155 @@ impl FigureTracker {
         });
     }
 
+    /// updates the item linked to the JAN/EAN with the passed values
+    pub fn update_item(&self, update_item: &UpdateItem) {
+        match self.db.as_ref().unwrap().get_item(update_item.jan) {
+            Ok(mut item) => {
+                if update_item.update_info {
+                    if let Err(err) = self
+                        .module_pool
+                        .as_ref()
+                        .unwrap()
+                        .update_info(item.borrow_mut())
+                    {
+                        warn!("unable to find figure information (err: {:?})", err)
+                    }
+                }
+
+                // manually passed values always have priority over the retrieved information
+                if let Some(description) = &update_item.description {
+                    item.description = description.clone();
+                }
+
+                if let Some(image) = &update_item.image {
+                    item.image = image.clone();
+                }
+
+                if let Some(term_en) = &update_item.term_en {
+                    item.term_en = term_en.clone();
+                }
+
+                if let Some(term_jp) = &update_item.term_jp {
+                    item.term_jp = term_jp.clone();
+                }
+
+                if let Some(disabled) = update_item.disabled {
+                    item.disabled = disabled;
+                }
+
+                match self.db.as_ref().unwrap().update_item(&item) {
+                    Ok(_) => info!("updated item: {:?} (JAN {})", item.description, item.jan),
+                    Err(err) => error!("unable to update item in the database (err: {:?})", err),
+                }
+            }
+            Err(err) => warn!("unable to retrieve item from the database (err: {:?})", err),
+        }
+    }
+
+    /// removes or archives the passed items including their prices and conditions
+    pub fn remove_items(&self, remove_item: &RemoveItem) {
+        let db = self.db.as_ref().unwrap();
+        remove_item
+            .items
+            .iter()
+            .for_each(|jan| match db.get_item(*jan) {
+                Ok(item) => {
+                    let res = if remove_item.archive {
+                        db.archive_item(&item)
+                    } else {
+                        db.remove_item(&item)
+                    };
+
+                    match res {
+                        Ok(_) => info!(
+                            "{} item: {:?} (JAN {})",
+                            if remove_item.archive {
+                                "archived"
+                            } else {
+                                "removed"
+                            },
+                            item.description,
+                            item.jan
+                        ),
+                        Err(err) => {
+                            error!("unable to remove item from the database (err: {:?})", err)
+                        }
+                    }
+                }
+                Err(err) => warn!("unable to retrieve item from the database (err: {:?})", err),
+            });
+    }
+
+    /// disables or enables the price checks of the passed items
+    pub fn set_items_disabled(&self, jans: &ItemJans, disabled: bool) {
+        let db = self.db.as_ref().unwrap();
+        jans.items.iter().for_each(|jan| match db.get_item(*jan) {
+            Ok(mut item) => {
+                item.disabled = disabled;
+                match db.update_item(&item) {
+                    Ok(_) => info!(
+                        "{} item: {:?} (JAN {})",
+                        if disabled { "disabled" } else { "enabled" },
+                        item.description,
+                        item.jan
+                    ),
+                    Err(err) => error!("unable to update item in the database (err: {:?})", err),
+                }
+            }
+            Err(err) => warn!("unable to retrieve item from the database (err: {:?})", err),
+        });
+    }
+
+    /// removes the passed notification conditions
+    pub fn remove_conditions(&self, ids: &ConditionIds) {
+        let db = self.db.as_ref().unwrap();
+        ids.conditions
+            .iter()
+            .for_each(|id| match db.get_condition(*id) {
+                Ok(condition) => match db.remove_condition(&condition) {
+                    Ok(_) => info!("removed condition: {}", id),
+                    Err(err) => error!(
+                        "unable to remove condition from the database (err: {:?})",
+                        err
+                    ),
+                },
+                Err(err) => warn!(
+                    "unable to retrieve condition from the database (err: {:?})",
+                    err
+                ),
+            });
+    }
+
+    /// disables or enables the passed notification conditions
+    pub fn set_conditions_disabled(&self, ids: &ConditionIds, disabled: bool) {
+        let db = self.db.as_ref().unwrap();
+        ids.conditions
+            .iter()
+            .for_each(|id| match db.get_condition(*id) {
+                Ok(mut condition) => {
+                    condition.disabled = disabled;
+                    match db.update_condition(&condition) {
+                        Ok(_) => info!(
+                            "{} condition: {}",
+                            if disabled { "disabled" } else { "enabled" },
+                            id
+                        ),
+                        Err(err) => error!(
+                            "unable to update condition in the database (err: {:?})",
+                            err
+                        ),
+                    }
+                }
+                Err(err) => warn!(
+                    "unable to retrieve condition from the database (err: {:?})",
+                    err
+                ),
+            });
+    }
+
     /// update the information of the passed item using the MFC database
     pub fn update_info(&self, item: &mut Item) {
-        match self.module_pool.update_info(item) {
+        match self.module_pool.as_ref().unwrap().update_info(item) {
             Ok(_) => match self.db.as_ref().unwrap().update_item(&item) {
                 Ok(_) => {}
                 Err(err) => warn!("unable to update figure information (err: {:?})", err),
@@ -196,75 +504,781 @@ impl FigureTracker {
         }
     }
 
+    /// imports the exchange rates of the requested ECB feed into the exchange rate history
+    pub fn update_rates(&self, update_rates: &UpdateRates) {
+        let rates = Client::new(&self.config.connection)
+            .and_then(|client| Ecb::get_feed_rates(&client, update_rates.feed));
+
+        match rates {
+            Ok(rates) => match self.db.as_ref().unwrap().add_exchange_rates(&rates) {
+                Ok(_) => info!("imported {} exchange rates", rates.len()),
+                Err(err) => error!("unable to save the exchange rates (err: {:?})", err),
+            },
+            Err(err) => error!("unable to retrieve the exchange rates (err: {:?})", err),
+        }
+    }
+
+    /// recomputes the converted prices of all recorded prices with the requested exchange rates
+    pub fn reconvert_prices(&self, reconvert: &Reconvert) {
+        let db = self.db.as_ref().unwrap();
+        let prices = match db.get_prices(None, None) {
+            Ok(prices) => prices,
+            Err(err) => {
+                error!(
+                    "unable to retrieve prices from the database (err: {:?})",
+                    err
+                );
+                return;
+            }
+        };
+
+        let mut conversions: HashMap<Option<NaiveDate>, CurrencyConversion> = HashMap::new();
+        let mut reconverted = 0;
+        let mut skipped = 0;
+
+        let res = db.transaction(|_| -> Result<(), Box<dyn Error>> {
+            for price in prices {
+                let date = match reconvert.rate {
+                    ReconvertRate::Listing => Some(price.timestamp.naive_utc().date()),
+                    ReconvertRate::Current => None,
+                };
+
+                if !conversions.contains_key(&date) {
+                    let rates = match date {
+                        Some(date) => db.get_exchange_rates_at(date)?,
+                        None => db.get_latest_exchange_rates()?,
+                    };
+                    conversions.insert(date, CurrencyConversion::from_rates(&rates));
+                }
+
+                let conversion = conversions.get(&date).unwrap();
+                // the costs are in the previous currency of the converted price
+                let convert =
+                    |money: &Money| conversion.convert_price_to(money, reconvert.to.clone());
+                let converted = convert(&price.price).and_then(|converted_price| {
+                    Ok(Price {
+                        converted_price,
+                        shipping: convert(&price.shipping)?,
+                        fees: convert(&price.fees)?,
+                        duty: convert(&price.duty)?,
+                        clearance_fee: convert(&price.clearance_fee)?,
+                        ..price.clone()
+                    })
+                });
+                match converted {
+                    Ok(price) => {
+                        db.update_price_conversion(&price)?;
+                        reconverted += 1;
+                    }
+                    Err(err) => {
+                        warn!(
+                            "no exchange rate for {} on {} found, skipping price {:?} (err: {:?})",
+                            price.price.currency,
+                            price.timestamp.naive_utc().date(),
+                            price.id,
+                            err
+                        );
+                        skipped += 1;
+                    }
+                }
+            }
+
+            Ok(())
+        });
+
+        match res {
+            Ok(_) => {
+                info!("reconverted {} prices to {}", reconverted, reconvert.to);
+                if skipped > 0 {
+                    warn!(
+                        "skipped {} prices without known exchange rates, \
+                         import the exchange rate history with \"update rates --feed full\"",
+                        skipped
+                    );
+                }
+            }
+            Err(err) => error!("unable to reconvert the prices (err: {:?})", err),
+        }
+    }
+
     /// updates the prices of all tracked items
     pub fn update_prices(&self) {
         match self.db.as_ref().unwrap().get_items() {
             Ok(items) => {
                 let mut notification_handles = vec![];
                 for item in items {
-                    info!(
-                        "updating prices for item: {:?} (JAN {})",
-                        item.description, item.jan
-                    );
+                    notification_handles.append(&mut self.update_item_prices(item));
+                }
+
+                // wait for all notifications before shutting down the application
+                self.record_notifications(notification_handles);
+            }
+            Err(err) => warn!(
+                "unable to retrieve items from the database (err: {:?})",
+                err
+            ),
+        }
+    }
+
+    /// keeps running and updates the prices of the tracked items in the configured intervals
+    /// until SIGINT or SIGTERM is received
+    pub fn run_daemon(&mut self, run: &Run) {
+        daemon::register_signal_handlers();
+
+        let interval = match run.interval {
+            Some(minutes) => time::Duration::from_secs(minutes * 60),
+            None => self.config.daemon.get_interval(),
+        };
+        let mut scheduler = Scheduler::new(
+            interval,
+            self.config.daemon.get_jitter(),
+            self.config.daemon.get_item_intervals(),
+        );
+
+        info!("starting daemon mode (interval: {:?})", interval);
 
-                    let new_prices = self.module_pool.check_item(item.clone());
-                    let current_time = Utc::now();
-                    for mut price in new_prices.clone() {
-                        price.timestamp = current_time;
-                        if let Err(err) = self.db.as_ref().unwrap().add_price(&price) {
-                            warn!("unable to add price to the database (err: {:?})", err)
+        while !daemon::is_shutdown_requested() {
+            let mut notification_handles = vec![];
+            match self.db.as_ref().unwrap().get_items() {
+                Ok(items) => {
+                    for item in items {
+                        if daemon::is_shutdown_requested() {
+                            break;
+                        }
+
+                        let now = time::Instant::now();
+                        if scheduler.is_due(&item, now) {
+                            scheduler.schedule(&item, now);
+                            if self.module_pool.as_ref().unwrap().has_expired_rates() {
+                                self.refresh_module_pool();
+                            }
+                            notification_handles.append(&mut self.update_item_prices(item));
                         }
                     }
+                }
+                Err(err) => warn!(
+                    "unable to retrieve items from the database (err: {:?})",
+                    err
+                ),
+            }
+
+            // let the notifications of this run finish, even if a shutdown got requested
+            self.record_notifications(notification_handles);
+
+            // check at least every minute for newly added or enabled items
+            let max_sleep = time::Instant::now() + time::Duration::from_secs(60);
+            daemon::sleep_until(match scheduler.get_next_check() {
+                Some(next_check) if next_check < max_sleep => next_check,
+                _ => max_sleep,
+            });
+        }
+
+        info!("received shutdown signal, stopped daemon mode");
+    }
+
+    /// recreate the module pool to retrieve the current exchange rates,
+    /// the previous module pool is kept if no exchange rates are available
+    fn refresh_module_pool(&mut self) {
+        info!("exchange rates expired, refreshing the exchange rates of the modules");
+        match ModulePool::new(&self.config, self.db.as_ref().unwrap()) {
+            Ok(module_pool) => self.module_pool = Some(module_pool),
+            Err(err) => warn!(
+                "unable to refresh the exchange rates, using the previous exchange rates (err: {:?})",
+                err
+            ),
+        }
+    }
+
+    /// updates the prices of the passed item and checks the related conditions
+    /// the returned handles belong to the triggered notifications
+    fn update_item_prices(&self, item: Item) -> Vec<NotificationHandle> {
+        info!(
+            "updating prices for item: {:?} (JAN {})",
+            item.description, item.jan
+        );
+
+        let db = self.db.as_ref().unwrap();
+        let current_time = Utc::now();
+        let known_listings = match db.get_listings(Some(item.id), None) {
+            Ok(listings) => listings,
+            Err(err) => {
+                warn!(
+                    "unable to retrieve listings from the database (err: {:?})",
+                    err
+                );
+                vec![]
+            }
+        };
 
-                    for handle in self.check_conditions(item, new_prices) {
-                        notification_handles.push(handle);
+        let item_check = self.module_pool.as_ref().unwrap().check_item(item.clone());
+        let mut listings = item_check.listings;
+        let mut listing_events = vec![];
+        for listing in listings.iter_mut() {
+            listing.price.timestamp = current_time;
+            listing.first_seen = current_time;
+            let event = listing.get_event(&known_listings);
+            match db.record_listing(listing) {
+                Ok(id) => {
+                    listing.id = Some(id);
+                    if let Some(event) = event {
+                        info!(
+                            "[{}] - detected listing event {:?} for {:?} ({})",
+                            listing.price.module, event, item.description, listing.price.url
+                        );
+                        listing_events.push((listing.clone(), event));
                     }
                 }
+                Err(err) => warn!("unable to add listing to the database (err: {:?})", err),
+            }
+        }
 
-                // wait for all notifications before shutting down the application
-                for notification_handle in notification_handles {
-                    notification_handle
-                        .join()
-                        .expect("Couldn't join on the associated thread");
+        for mut sale in item_check.sales {
+            sale.price.timestamp = current_time;
+            match db.record_sale(&sale) {
+                Ok(true) => info!(
+                    "[{}] - recorded sale of {:?} for {} ({})",
+                    sale.price.module, item.description, sale.price.price, sale.price.url
+                ),
+                Ok(false) => {}
+                Err(err) => warn!("unable to add sale to the database (err: {:?})", err),
+            }
+        }
+
+        // only the modules which were checked successfully can tell if a listing is gone
+        for module in item_check.modules.iter() {
+            match db.mark_sold_out_listings(item.id, module, current_time) {
+                Ok(0) => {}
+                Ok(sold_out) => info!(
+                    "[{}] - {} listings of {:?} sold out",
+                    module, sold_out, item.description
+                ),
+                Err(err) => warn!(
+                    "unable to mark sold out listings in the database (err: {:?})",
+                    err
+                ),
+            }
+        }
+
+        let mut new_prices: Vec<Price> = vec![];
+        for mut price in ModulePool::get_lowest_prices(&item, &listings) {
+            match db.add_price(&price) {
+                Ok(id) => {
+                    price.id = Some(id);
+                    new_prices.push(price);
                 }
+                Err(err) => warn!("unable to add price to the database (err: {:?})", err),
             }
-            Err(err) => warn!(
+        }
+
+        self.check_conditions(item, new_prices, listings, listing_events)
+    }
+
+    /// waits for the passed notification threads and records the sent notifications,
+    /// panicked notification threads are recorded as failed notifications
+    fn record_notifications(&self, notification_handles: Vec<NotificationHandle>) {
+        for notification_handle in notification_handles {
+            for notification in notification_handle.join() {
+                if let Err(err) = self.db.as_ref().unwrap().add_notification(&notification) {
+                    warn!(
+                        "unable to add notification to the database (err: {:?})",
+                        err
+                    )
+                }
+            }
+        }
+    }
+
+    /// retrieve all items including the disabled items mapped by their ID
+    fn get_item_map(&self) -> HashMap<i64, Item> {
+        match self.db.as_ref().unwrap().get_all_items() {
+            Ok(items) => items.into_iter().map(|item| (item.id, item)).collect(),
+            Err(err) => {
+                warn!(
+                    "unable to retrieve items from the database (err: {:?})",
+                    err
+                );
+                HashMap::new()
+            }
+        }
+    }
+
+    /// prints all tracked items in the requested output format
+    pub fn list_items(&self, list_items: &ListItems) {
+        match self.db.as_ref().unwrap().get_all_items() {
+            Ok(items) => {
+                let mut table = Table::new(vec![
+                    "id",
+                    "jan",
+                    "description",
+                    "term_en",
+                    "term_jp",
+                    "disabled",
+                ]);
+
+                for item in items {
+                    table.add_row(vec![
+                        Value::from(item.id),
+                        Value::from(item.jan),
+                        Value::from(item.description),
+                        Value::from(item.term_en),
+                        Value::from(item.term_jp),
+                        Value::from(item.disabled),
+                    ]);
+                }
+
+                println!("{}", table.render(list_items.format));
+            }
+            Err(err) => error!(
                 "unable to retrieve items from the database (err: {:?})",
                 err
             ),
         }
     }
 
-    /// check the found prices with the currently saved notifications
-    pub fn check_conditions(&self, item: Item, prices: Vec<Price>) -> Vec<JoinHandle<()>> {
+    /// prints the conditions of all items or of the passed item in the requested output format
+    pub fn list_conditions(&self, list_conditions: &ListConditions) {
+        let db = self.db.as_ref().unwrap();
+        let conditions = match list_conditions.jan {
+            Some(jan) => match db.get_item(jan) {
+                Ok(item) => db.get_related_conditions(item),
+                Err(err) => Err(err),
+            },
+            None => db.get_conditions(),
+        };
+
+        match conditions {
+            Ok(conditions) => {
+                let items = self.get_item_map();
+                let mut table = Table::new(vec![
+                    "id",
+                    "jan",
+                    "description",
+                    "type",
+                    "condition",
+                    "min_item_grade",
+                    "min_box_grade",
+                    "pre_orders",
+                    "buyout_only",
+                    "ending_within",
+                    "value",
+                    "disabled",
+                ]);
+
+                for condition in conditions {
+                    let item = items.get(&condition.item_id);
+                    table.add_row(vec![
+                        Value::from(condition.id),
+                        Value::from(item.map(|item| item.jan)),
+                        Value::from(item.map(|item| item.description.clone())),
+                        Value::from(condition.condition_type.to_string()),
+                        Value::from(format!("{:?}", condition.item_condition).to_lowercase()),
+                        Value::from(condition.min_item_grade.map(|grade| grade.to_string())),
+                        Value::from(condition.min_box_grade.map(|grade| grade.to_string())),
+                        Value::from(condition.pre_orders.to_string()),
+                        Value::from(condition.buyout_only),
+                        Value::from(condition.ending_within),
+                        Value::from(condition.value),
+                        Value::from(condition.disabled),
+                    ]);
+                }
+
+                println!("{}", table.render(list_conditions.format));
+            }
+            Err(err) => error!(
+                "unable to retrieve conditions from the database (err: {:?})",
+                err
+            ),
+        }
+    }
+
+    /// prints the recorded prices of all items or of the passed item in the requested output format
+    pub fn list_prices(&self, list_prices: &ListPrices) {
+        let db = self.db.as_ref().unwrap();
+        let item_id = match list_prices.jan {
+            Some(jan) => match db.get_item(jan) {
+                Ok(item) => Some(item.id),
+                Err(err) => {
+                    error!("unable to retrieve item from the database (err: {:?})", err);
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        match db.get_prices(item_id, list_prices.since) {
+            Ok(prices) => {
+                let items = self.get_item_map();
+                let mut table = Table::new(vec![
+                    "id",
+                    "jan",
+                    "module",
+                    "condition",
+                    "price",
+                    "currency",
+                    "converted_price",
+                    "total",
+                    "converted_currency",
+                    "url",
+                    "timestamp",
+                ]);
+
+                for price in prices {
+                    table.add_row(vec![
+                        Value::from(price.id),
+                        Value::from(items.get(&price.item_id).map(|item| item.jan)),
+                        Value::from(price.module.clone()),
+                        Value::from(format!("{:?}", price.condition).to_lowercase()),
+                        Value::from(price.price.to_major()),
+                        Value::from(price.price.currency.to_string()),
+                        Value::from(price.converted_price.to_major()),
+                        Value::from(price.get_converted_total().to_major()),
+                        Value::from(price.converted_price.currency.to_string()),
+                        Value::from(price.url.clone()),
+                        Value::from(price.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    ]);
+                }
+
+                println!("{}", table.render(list_prices.format));
+            }
+            Err(err) => error!(
+                "unable to retrieve prices from the database (err: {:?})",
+                err
+            ),
+        }
+    }
+
+    /// prints the recorded listings of all items or of the passed item in the requested output format
+    pub fn list_listings(&self, list_listings: &ListListings) {
+        let db = self.db.as_ref().unwrap();
+        let item_id = match list_listings.jan {
+            Some(jan) => match db.get_item(jan) {
+                Ok(item) => Some(item.id),
+                Err(err) => {
+                    error!("unable to retrieve item from the database (err: {:?})", err);
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        match db.get_listings(item_id, list_listings.since) {
+            Ok(listings) => {
+                let items = self.get_item_map();
+                let mut table = Table::new(vec![
+                    "id",
+                    "jan",
+                    "module",
+                    "seller",
+                    "condition",
+                    "grade",
+                    "item_grade",
+                    "box_grade",
+                    "in_stock",
+                    "availability",
+                    "release_month",
+                    "buyout_price",
+                    "auction_end",
+                    "price",
+                    "currency",
+                    "total",
+                    "converted_currency",
+                    "url",
+                    "first_seen",
+                    "last_seen",
+                    "sold_out_at",
+                    "days_listed",
+                ]);
+
+                for listing in listings {
+                    if list_listings.sold_out && listing.sold_out_at.is_none() {
+                        continue;
+                    }
+
+                    let price = &listing.price;
+                    table.add_row(vec![
+                        Value::from(listing.id),
+                        Value::from(items.get(&price.item_id).map(|item| item.jan)),
+                        Value::from(price.module.clone()),
+                        Value::from(listing.seller.clone()),
+                        Value::from(format!("{:?}", price.condition).to_lowercase()),
+                        Value::from(listing.grade.clone()),
+                        Value::from(price.item_grade.map(|grade| grade.to_string())),
+                        Value::from(price.box_grade.map(|grade| grade.to_string())),
+                        Value::from(listing.in_stock),
+                        Value::from(price.availability.to_string()),
+                        Value::from(
+                            price
+                                .release_month
+                                .map(|release_month| release_month.format("%Y-%m").to_string()),
+                        ),
+                        Value::from(
+                            price
+                                .buyout_price
+                                .as_ref()
+                                .map(|buyout_price| buyout_price.to_major()),
+                        ),
+                        Value::from(price.auction_end.map(|auction_end| {
+                            auction_end.format("%Y-%m-%d %H:%M:%S").to_string()
+                        })),
+                        Value::from(price.price.to_major()),
+                        Value::from(price.price.currency.to_string()),
+                        Value::from(price.get_converted_total().to_major()),
+                        Value::from(price.converted_price.currency.to_string()),
+                        Value::from(price.url.clone()),
+                        Value::from(listing.first_seen.format("%Y-%m-%d %H:%M:%S").to_string()),
+                        Value::from(
+                            listing
+                                .get_last_seen()
+                                .format("%Y-%m-%d %H:%M:%S")
+                                .to_string(),
+                        ),
+                        Value::from(listing.sold_out_at.map(|sold_out_at| {
+                            sold_out_at.format("%Y-%m-%d %H:%M:%S").to_string()
+                        })),
+                        Value::from(listing.get_lifetime().map(|lifetime| {
+                            (lifetime.num_minutes() as f64 / 144.0).round() / 10.0
+                        })),
+                    ]);
+                }
+
+                println!("{}", table.render(list_listings.format));
+            }
+            Err(err) => error!(
+                "unable to retrieve listings from the database (err: {:?})",
+                err
+            ),
+        }
+    }
+
+    /// prints the recorded sales of all items or of the passed item in the requested output format
+    pub fn list_sales(&self, list_sales: &ListSales) {
+        let db = self.db.as_ref().unwrap();
+        let item_id = match list_sales.jan {
+            Some(jan) => match db.get_item(jan) {
+                Ok(item) => Some(item.id),
+                Err(err) => {
+                    error!("unable to retrieve item from the database (err: {:?})", err);
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        match db.get_sales(item_id, list_sales.since) {
+            Ok(sales) => {
+                let items = self.get_item_map();
+                let mut table = Table::new(vec![
+                    "id",
+                    "jan",
+                    "module",
+                    "seller",
+                    "condition",
+                    "item_grade",
+                    "box_grade",
+                    "price",
+                    "currency",
+                    "total",
+                    "converted_currency",
+                    "url",
+                    "sold_at",
+                ]);
+
+                for sale in sales {
+                    let price = &sale.price;
+                    table.add_row(vec![
+                        Value::from(sale.id),
+                        Value::from(items.get(&price.item_id).map(|item| item.jan)),
+                        Value::from(price.module.clone()),
+                        Value::from(sale.seller.clone()),
+                        Value::from(format!("{:?}", price.condition).to_lowercase()),
+                        Value::from(price.item_grade.map(|grade| grade.to_string())),
+                        Value::from(price.box_grade.map(|grade| grade.to_string())),
+                        Value::from(price.price.to_major()),
+                        Value::from(price.price.currency.to_string()),
+                        Value::from(price.get_converted_total().to_major()),
+                        Value::from(price.converted_price.currency.to_string()),
+                        Value::from(price.url.clone()),
+                        Value::from(sale.sold_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    ]);
+                }
+
+                println!("{}", table.render(list_sales.format));
+            }
+            Err(err) => error!(
+                "unable to retrieve sales from the database (err: {:?})",
+                err
+            ),
+        }
+    }
+
+    /// prints the sent notifications of all items or of the passed item in the requested output format
+    pub fn history_notifications(&self, history_notifications: &HistoryNotifications) {
+        let db = self.db.as_ref().unwrap();
+        let item_id = match history_notifications.jan {
+            Some(jan) => match db.get_item(jan) {
+                Ok(item) => Some(item.id),
+                Err(err) => {
+                    error!("unable to retrieve item from the database (err: {:?})", err);
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        match db.get_notifications(item_id, history_notifications.since) {
+            Ok(notifications) => {
+                let items = self.get_item_map();
+                let mut table = Table::new(vec![
+                    "id",
+                    "timestamp",
+                    "jan",
+                    "condition_id",
+                    "type",
+                    "channel",
+                    "success",
+                    "error",
+                    "module",
+                    "total",
+                    "converted_currency",
+                    "url",
+                ]);
+
+                for notification in notifications {
+                    let price = db.get_price(notification.price_id).ok();
+                    table.add_row(vec![
+                        Value::from(notification.id),
+                        Value::from(
+                            notification
+                                .timestamp
+                                .format("%Y-%m-%d %H:%M:%S")
+                                .to_string(),
+                        ),
+                        Value::from(items.get(&notification.item_id).map(|item| item.jan)),
+                        Value::from(notification.condition_id),
+                        Value::from(notification.condition_type.to_string()),
+                        Value::from(notification.channel),
+                        Value::from(notification.success),
+                        Value::from(notification.error),
+                        Value::from(price.as_ref().map(|price| price.module.clone())),
+                        Value::from(
+                            price
+                                .as_ref()
+                                .map(|price| price.get_converted_total().to_major()),
+                        ),
+                        Value::from(
+                            price
+                                .as_ref()
+                                .map(|price| price.converted_price.currency.to_string()),
+                        ),
+                        Value::from(price.as_ref().map(|price| price.url.clone())),
+                    ]);
+                }
+
+                println!("{}", table.render(history_notifications.format));
+            }
+            Err(err) => error!(
+                "unable to retrieve notifications from the database (err: {:?})",
+                err
+            ),
+        }
+    }
+
+    /// check if the condition already notified over the channel about the same listing
+    /// with an unchanged price during the configured cooldown
+    fn is_repeated_notification(
+        &self,
+        price: &Price,
+        condition: &Condition,
+        channel: &str,
+    ) -> bool {
+        let since = Utc::now() - self.config.notifications.get_cooldown();
+        match self
+            .db
+            .as_ref()
+            .unwrap()
+            .get_last_notified_price(condition, price, channel, since)
+        {
+            // buyout conditions don't notify again about changed bids of auctions
+            Ok(Some(last_price)) if condition.buyout_only && price.is_auction() => {
+                last_price.buyout_price == price.buyout_price
+            }
+            Ok(Some(last_price)) => last_price.price == price.price,
+            Ok(None) => false,
+            Err(err) => {
+                warn!(
+                    "unable to retrieve previous notifications from the database (err: {:?})",
+                    err
+                );
+                false
+            }
+        }
+    }
+
+    /// check the found prices, listings and listing events with the currently saved notifications
+    /// the returned handles return the sent notifications which still have to be recorded
+    pub fn check_conditions(
+        &self,
+        item: Item,
+        prices: Vec<Price>,
+        listings: Vec<Listing>,
+        listing_events: Vec<(Listing, ListingEvent)>,
+    ) -> Vec<NotificationHandle> {
         let mut handles = vec![];
+        let mut recorded_prices = prices.clone();
+
+        let related_conditions = match self
+            .db
+            .as_ref()
+            .unwrap()
+            .get_related_conditions(item.clone())
+        {
+            Ok(related_conditions) => related_conditions,
+            Err(_) => return handles,
+        };
 
-        for price in prices {
-            if let Ok(related_conditions) = self
-                .db
-                .as_ref()
-                .unwrap()
-                .get_related_conditions(item.clone())
-            {
-                for condition in related_conditions {
+        for condition in related_conditions {
+            if condition.disabled {
+                continue;
+            }
+
+            if condition.is_auction_condition() {
+                // the lowest prices of auctions are their current bids,
+                // so auction conditions are checked against every listing
+                for listing in listings.iter().filter(|listing| listing.in_stock) {
+                    if self
+                        .db
+                        .as_ref()
+                        .unwrap()
+                        .matches_condition(listing.price.clone(), condition.clone())
+                    {
+                        if let Some(handle) =
+                            self.notify_listing(&item, listing, &condition, &mut recorded_prices)
+                        {
+                            handles.push(handle);
+                        }
+                    }
+                }
+            } else {
+                for price in prices.iter() {
                     if self
                         .db
                         .as_ref()
                         .unwrap()
                         .matches_condition(price.clone(), condition.clone())
                     {
-                        let not = self.notifications.clone();
-                        let shared_item = item.clone();
-                        let shared_price = price.clone();
-                        handles.push(thread::spawn(move || {
-                            match not.notify(shared_item, shared_price, condition) {
-                                Ok(_) => info!("notified about condition match..."),
-                                Err(err) => {
-                                    warn!("couldn't notify about condition match (err: {:?})", err)
-                                }
-                            }
-                        }));
-                        continue;
+                        if let Some(handle) = self.notify(&item, price, &condition) {
+                            handles.push(handle);
+                        }
+                    }
+                }
+            }
+
+            for (listing, event) in listing_events.iter() {
+                if listing.matches_condition(*event, &condition) {
+                    if let Some(handle) =
+                        self.notify_listing(&item, listing, &condition, &mut recorded_prices)
+                    {
+                        handles.push(handle);
                     }
                 }
             }
@@ -272,6 +1286,158 @@ impl FigureTracker {
 
         handles
     }
+
+    /// notify about the listing matching the condition, notifications reference a price,
+    /// so the price of the listing is recorded unless it is already one of the recorded prices
+    fn notify_listing(
+        &self,
+        item: &Item,
+        listing: &Listing,
+        condition: &Condition,
+        recorded_prices: &mut Vec<Price>,
+    ) -> Option<NotificationHandle> {
+        let recorded_price = recorded_prices
+            .iter()
+            .find(|price| {
+                price.module == listing.price.module
+                    && price.url == listing.price.url
+                    && price.condition == listing.price.condition
+                    && price.price == listing.price.price
+            })
+            .cloned();
+
+        let price = match recorded_price {
+            Some(price) => price,
+            None => {
+                let mut price = listing.price.clone();
+                match self.db.as_ref().unwrap().add_price(&price) {
+                    Ok(id) => price.id = Some(id),
+                    Err(err) => {
+                        warn!("unable to add price to the database (err: {:?})", err);
+                        return None;
+                    }
+                }
+                recorded_prices.push(price.clone());
+                price
+            }
+        };
+
+        self.notify(item, &price, condition)
+    }
+
+    /// notify about the price matching the condition in a separate thread over all channels
+    /// which didn't already notify about the unchanged price, so failed channels are retried
+    /// without repeating the notification over the successful channels
+    fn notify(
+        &self,
+        item: &Item,
+        price: &Price,
+        condition: &Condition,
+    ) -> Option<NotificationHandle> {
+        let channels: Vec<&'static str> = self
+            .notifications
+            .get_channels()
+            .into_iter()
+            .filter(|channel| !self.is_repeated_notification(price, condition, channel))
+            .collect();
+        if channels.is_empty() {
+            info!(
+                "skipping notification for unchanged price of {:?} ({})",
+                item.description, price.url
+            );
+            return None;
+        }
+
+        let failure = Notification {
+            id: None,
+            item_id: item.id,
+            price_id: price.id.unwrap(),
+            condition_id: condition.id,
+            condition_type: condition.condition_type,
+            channel: "".to_string(),
+            success: false,
+            error: "".to_string(),
+            timestamp: Utc::now(),
+        };
+
+        let not = self.notifications.clone();
+        let shared_item = item.clone();
+        let shared_price = price.clone();
+        let condition = condition.clone();
+        let shared_channels = channels.clone();
+        let handle = thread::spawn(move || {
+            let mut notifications = vec![];
+            for result in not.notify(
+                shared_item.clone(),
+                shared_price.clone(),
+                condition.clone(),
+                &shared_channels,
+            ) {
+                match &result.error {
+                    None => info!("notified about condition match via {}...", result.channel),
+                    Some(err) => warn!(
+                        "couldn't notify about condition match via {} (err: {:?})",
+                        result.channel, err
+                    ),
+                }
+
+                notifications.push(Notification {
+                    id: None,
+                    item_id: shared_item.id,
+                    price_id: shared_price.id.unwrap(),
+                    condition_id: condition.id,
+                    condition_type: condition.condition_type,
+                    channel: result.channel,
+                    success: result.error.is_none(),
+                    error: result.error.unwrap_or_default(),
+                    timestamp: Utc::now(),
+                });
+            }
+
+            notifications
+        });
+
+        Some(NotificationHandle {
+            handle,
+            channels,
+            failure,
+        })
+    }
+
+    /// prints the migration status or migrates the database to the requested schema version
+    pub fn migrate_database(&self, migrate: &Migrate) {
+        let db = self.db.as_ref().unwrap();
+        let version = match db.get_schema_version() {
+            Ok(version) => version,
+            Err(err) => {
+                error!("unable to retrieve the schema version (err: {:?})", err);
+                return;
+            }
+        };
+
+        if migrate.status {
+            let mut table = Table::new(vec!["version", "description", "applied"]);
+            for step in MIGRATIONS.iter() {
+                table.add_row(vec![
+                    Value::from(step.version),
+                    Value::from(step.description),
+                    Value::from(step.version <= version),
+                ]);
+            }
+
+            println!("{}", table.render(migrate.format));
+            return;
+        }
+
+        let target = migrate.to.unwrap_or_else(get_latest_version);
+        match db.migrate(target) {
+            Ok(_) => info!("migrated database from version {} to {}", version, target),
+            Err(err) => error!(
+                "unable to migrate the database to version {} (err: {:?})",
+                target, err
+            ),
+        }
+    }
 }
 
 fn main() {
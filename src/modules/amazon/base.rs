@@ -5,25 +5,37 @@ use regex::Regex;
 
 use crate::currency::guesser::CurrencyGuesser;
 use crate::currency::SupportedCurrency;
-use crate::database::items::{Item, ItemConditions};
+use crate::database::items::{Grade, Item, ItemConditions};
+use crate::database::listings::Listing;
 use crate::database::prices::Price;
+use crate::http::Client;
 use crate::modules::amazon::AmazonCoJp;
-use crate::modules::{BaseModule, Prices};
+use crate::modules::BaseModule;
 
 struct Base<'a> {
-    pub(crate) client: &'a reqwest::blocking::Client,
+    pub(crate) client: &'a Client,
 }
 
 impl<'a> Base<'a> {
+    /// map the condition of used marketplace offers to the item grade, the box isn't graded
+    fn get_item_grade(condition: &str) -> Option<Grade> {
+        match condition.trim_start_matches("Used - ") {
+            "Like New" => Some(Grade::A),
+            "Very Good" => Some(Grade::B),
+            "Good" | "Acceptable" => Some(Grade::C),
+            _ => None,
+        }
+    }
+
     // don't ask me how but reqwest fools the IntelliJ plugin quite good
     //noinspection RsUnresolvedReference
-    fn get_lowest_price_from_url(
+    fn get_listings_from_url(
         &self,
         item: Item,
         url: String,
         condition: ItemConditions,
-    ) -> Result<Option<Price>, Box<dyn Error>> {
-        let mut prices = vec![];
+    ) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
 
         let res = self.client.get(url.as_str()).send()?;
         let doc = kuchiki::parse_html().one(res.text()?.as_str());
@@ -36,46 +48,43 @@ impl<'a> Base<'a> {
                 .select_first("strong.text-green.price")
                 .unwrap()
                 .text_contents();
-
-            prices.push(Price::new(
-                item.clone(),
-                CurrencyGuesser::get_currency_value(price_text)?,
-                SupportedCurrency::JPY,
-                url.clone(),
-                AmazonCoJp::get_module_key(),
-                condition,
+            let seller = match sale_node.select_first("div.seller-name") {
+                Ok(seller) => seller.text_contents().trim().to_string(),
+                Err(_) => "".to_string(),
+            };
+            let grade = match sale_node.select_first("div.condition") {
+                Ok(grade) => grade.text_contents().trim().to_string(),
+                Err(_) => "".to_string(),
+            };
+
+            listings.push(Listing::new(
+                Price::new(
+                    item.clone(),
+                    CurrencyGuesser::get_currency_value(price_text, SupportedCurrency::JPY)?,
+                    url.clone(),
+                    AmazonCoJp::get_module_key(),
+                    condition,
+                )
+                .with_grades(Base::get_item_grade(&grade), None),
+                seller,
+                grade,
             ));
         }
 
-        let mut lowest_price: Option<Price> = None;
-        for price in prices {
-            if lowest_price.is_none() || price.price < lowest_price.clone().unwrap().price {
-                lowest_price = Some(price)
-            }
-        }
-
-        Ok(lowest_price)
+        Ok(listings)
     }
 
-    fn get_lowest_new_price(
-        &self,
-        item: Item,
-        asin: &'a str,
-    ) -> Result<Option<Price>, Box<dyn Error>> {
+    fn get_new_listings(&self, item: Item, asin: &'a str) -> Result<Vec<Listing>, Box<dyn Error>> {
         let search_url = format!(
             "https://neokyo.com/en/amazon-marketplace-listing\
             ?provider=amazonJapan&asin={}&item_title=&new=true",
             asin
         );
 
-        self.get_lowest_price_from_url(item, search_url, ItemConditions::New)
+        self.get_listings_from_url(item, search_url, ItemConditions::New)
     }
 
-    fn get_lowest_used_price(
-        &self,
-        item: Item,
-        asin: &'a str,
-    ) -> Result<Option<Price>, Box<dyn Error>> {
+    fn get_used_listings(&self, item: Item, asin: &'a str) -> Result<Vec<Listing>, Box<dyn Error>> {
         let search_url = format!(
             "https://neokyo.com/en/amazon-marketplace-listing\
              ?provider=amazonJapan\
@@ -83,7 +92,7 @@ impl<'a> Base<'a> {
             asin
         );
 
-        self.get_lowest_price_from_url(item, search_url, ItemConditions::Used)
+        self.get_listings_from_url(item, search_url, ItemConditions::Used)
     }
 }
 
@@ -93,17 +102,14 @@ impl BaseModule for AmazonCoJp {
         AmazonCoJp::get_module_key()
     }
 
-    /// retrieve the lowest price for new and used condition
-    fn get_lowest_prices(&self, item: &Item) -> Result<Prices, Box<dyn Error>> {
+    /// retrieve the marketplace listings for new and used condition
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
         let search_url = format!(
             "https://neokyo.com/en/search-results?keyword={}&provider=amazonJapan&spid=",
             item.jan
         );
 
-        let mut prices = Prices {
-            used: None,
-            new: None,
-        };
+        let mut listings = vec![];
 
         let base = Base {
             client: &self.client,
@@ -120,21 +126,23 @@ impl BaseModule for AmazonCoJp {
 
                     if asin_regex.is_match(detail_link) {
                         if let Some(asin) = asin_regex.captures(detail_link).unwrap().name("asin") {
-                            prices.new = base.get_lowest_new_price(item.clone(), asin.as_str())?;
-                            prices.used =
-                                base.get_lowest_used_price(item.clone(), asin.as_str())?;
+                            listings.extend(base.get_new_listings(item.clone(), asin.as_str())?);
+                            listings.extend(base.get_used_listings(item.clone(), asin.as_str())?);
                         }
                     }
                 }
             }
         }
 
-        Ok(prices)
+        Ok(listings)
     }
 }
 
 #[test]
 pub fn test_get_lowest_prices() {
+    use crate::currency::money::Money;
+    use crate::modules::Module;
+
     let item = &mut Item {
         id: 0,
         jan: 4_934_054_783_441,
@@ -146,10 +154,30 @@ pub fn test_get_lowest_prices() {
     };
 
     let amazon = AmazonCoJp {
-        client: reqwest::blocking::Client::builder().build().unwrap(),
+        client: Client::from_fixture("amazon/lowest_prices"),
     };
 
-    if let Err(err) = amazon.get_lowest_prices(item) {
-        println!("{}", err)
-    }
+    // every marketplace offer is a listing of its seller
+    let listings = amazon.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 4);
+    assert_eq!(listings[1].seller, "Seller 2");
+    assert_eq!(
+        listings[1].price.price,
+        Money::new(14980, SupportedCurrency::JPY)
+    );
+    // used offers are graded by their marketplace condition
+    assert_eq!(listings[2].grade, "Used - Like New");
+    assert_eq!(listings[2].price.item_grade, Some(Grade::A));
+    assert_eq!(listings[3].price.item_grade, Some(Grade::C));
+
+    let prices = amazon.get_lowest_prices(item).unwrap();
+
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(14980, SupportedCurrency::JPY));
+    assert_eq!(new.condition, ItemConditions::New);
+    assert!(new.url.contains("asin=B07KQ7TPHQ"));
+
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(9800, SupportedCurrency::JPY));
+    assert_eq!(used.condition, ItemConditions::Used);
 }
@@ -1,28 +1,35 @@
 use std::error::Error;
 
+use regex::Regex;
+
+use crate::currency::money::Money;
 use crate::currency::SupportedCurrency;
-use crate::database::items::{Item, ItemConditions};
-use crate::database::prices::Price;
+use crate::database::items::{Grade, Item, ItemConditions};
+use crate::database::listings::Listing;
+use crate::database::prices::{Availability, Price};
 use crate::modules::amiami::info::Info;
 use crate::modules::amiami::AmiAmi;
-use crate::modules::{BaseModule, Prices};
+use crate::modules::BaseModule;
 
 impl BaseModule for AmiAmi {
     fn get_module_key(&self) -> String {
         AmiAmi::get_module_key()
     }
 
-    fn get_lowest_prices(&self, item: &Item) -> Result<Prices, Box<dyn Error>> {
-        let mut prices = Prices {
-            used: None,
-            new: None,
-        };
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+        // pre-owned items are prefixed with their grading, f.e. "(Pre-owned ITEM:A/BOX:B)"
+        let grade_regex = Regex::new(r"^\((?P<grade>Pre-owned[^)]*)\)")?;
+        let item_grade_regex = Regex::new(r"ITEM:(?P<grade>[^/)]+)")?;
+        let box_grade_regex = Regex::new(r"BOX:(?P<grade>[^/)]+)")?;
 
         let api_response = Info { inner: self }.search(item.jan.to_string())?;
         for api_item in api_response.items {
-            if api_item.instock_flg != 1 {
-                continue;
-            }
+            // items which were never available have no price
+            let min_price = match api_item.min_price {
+                Some(min_price) => min_price,
+                None => continue,
+            };
 
             let cond = if api_item.condition_flg == 0 {
                 ItemConditions::New
@@ -30,21 +37,101 @@ impl BaseModule for AmiAmi {
                 ItemConditions::Used
             };
 
-            let price = Price::new(
-                item.clone(),
-                api_item.min_price.unwrap() as f64,
-                SupportedCurrency::JPY,
-                api_item.get_figure_url(),
-                AmiAmi::get_module_key(),
-                cond,
-            );
+            let grade = match grade_regex.captures(api_item.gname.as_str()) {
+                Some(captures) => captures["grade"].to_string(),
+                None => "".to_string(),
+            };
+            let item_grade = item_grade_regex
+                .captures(grade.as_str())
+                .and_then(|captures| Grade::from_letter(&captures["grade"]));
+            // a box grade of "N" is used for items sold without their box
+            let box_grade = box_grade_regex
+                .captures(grade.as_str())
+                .and_then(|captures| match captures["grade"].trim() {
+                    "N" => Some(Grade::Junk),
+                    box_grade => Grade::from_letter(box_grade),
+                });
 
-            match cond {
-                ItemConditions::New => prices.new = Some(price),
-                ItemConditions::Used => prices.used = Some(price),
-                _ => {}
-            }
+            let availability = if api_item.preorderitem == 1 {
+                Availability::PreOrder
+            } else if api_item.backorderitem == 1 {
+                Availability::Backorder
+            } else {
+                Availability::InStock
+            };
+            let release_month = match availability {
+                Availability::PreOrder => api_item.get_release_month(),
+                _ => None,
+            };
+
+            let mut listing = Listing::new(
+                Price::new(
+                    item.clone(),
+                    Money::new(min_price as i64, SupportedCurrency::JPY),
+                    api_item.get_figure_url(),
+                    AmiAmi::get_module_key(),
+                    cond,
+                )
+                .with_grades(item_grade, box_grade)
+                .with_availability(availability, release_month),
+                "".to_string(),
+                grade,
+            );
+            listing.in_stock = api_item.instock_flg == 1;
+            listings.push(listing);
         }
-        Ok(prices)
+
+        Ok(listings)
     }
 }
+
+#[test]
+pub fn test_get_lowest_prices() {
+    use crate::http::Client;
+    use crate::modules::Module;
+    use chrono::NaiveDate;
+
+    let item = &mut Item {
+        id: 0,
+        jan: 6_971_995_420_057,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+
+    let amiami = AmiAmi {
+        client: Client::from_fixture("amiami/search"),
+    };
+
+    let listings = amiami.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 2);
+    assert_eq!(listings[1].grade, "Pre-owned ITEM:A-/BOX:B");
+    assert_eq!(listings[1].price.item_grade, Some(Grade::A));
+    assert_eq!(listings[1].price.box_grade, Some(Grade::B));
+    // the release month is only relevant for pre-orders
+    assert_eq!(listings[0].price.availability, Availability::PreOrder);
+    assert_eq!(
+        listings[0].price.release_month,
+        NaiveDate::from_ymd_opt(2021, 8, 1)
+    );
+    assert_eq!(listings[1].price.availability, Availability::InStock);
+    assert_eq!(listings[1].price.release_month, None);
+
+    let prices = amiami.get_lowest_prices(item).unwrap();
+
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(19800, SupportedCurrency::JPY));
+    assert_eq!(
+        new.url,
+        "https://www.amiami.com/eng/detail/?gcode=FIGURE-054261"
+    );
+
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(16480, SupportedCurrency::JPY));
+    assert_eq!(
+        used.url,
+        "https://www.amiami.com/eng/detail/?gcode=FIGURE-054261-R"
+    );
+}
@@ -1,6 +1,7 @@
 use std::error::Error;
 use std::result::Result::Err;
 
+use chrono::{Datelike, NaiveDate};
 use regex::Regex;
 use serde::Deserialize;
 
@@ -33,6 +34,11 @@ pub(crate) struct ApiItem {
     pub(crate) maker_name: String,
     pub(crate) instock_flg: u8,
     pub(crate) condition_flg: u8,
+    #[serde(default)]
+    pub(crate) preorderitem: u8,
+    #[serde(default)]
+    pub(crate) backorderitem: u8,
+    pub(crate) releasedate: Option<String>,
 }
 
 /// the _embedded part of the ApiSearchResponse, contains mostly metadata
@@ -66,6 +72,14 @@ impl ApiItem {
     pub fn get_figure_url(&self) -> String {
         format!("https://www.amiami.com/eng/detail/?gcode={}", self.gcode)
     }
+
+    /// retrieve the first day of the release month from the release date, f.e. "2021-08-31 00:00:00"
+    pub fn get_release_month(&self) -> Option<NaiveDate> {
+        let release_date = self.releasedate.as_ref()?;
+        NaiveDate::parse_from_str(release_date.get(..10)?, "%Y-%m-%d")
+            .ok()
+            .map(|release_date| release_date.with_day(1).unwrap())
+    }
 }
 
 pub(crate) struct Info<'a> {
@@ -140,6 +154,7 @@ impl InfoModule for AmiAmi {
 
 #[test]
 fn test_figure_url() {
+    use crate::http::Client;
     use crate::modules::InfoModule;
 
     let item = &mut Item {
@@ -152,9 +167,18 @@ fn test_figure_url() {
         disabled: false,
     };
 
-    assert!(AmiAmi::new().unwrap().update_figure_details(item).is_ok());
+    let amiami = AmiAmi {
+        client: Client::from_fixture("amiami/search"),
+    };
 
-    println!("{:?}", item.jan);
-    println!("{:?}", item.description);
-    println!("{:?}", item.term_en);
+    assert!(amiami.update_figure_details(item).is_ok());
+    assert_eq!(
+        item.description,
+        "Azur Lane Prinz Eugen Final Lap Ver. 1/7 Complete Figure"
+    );
+    assert_eq!(
+        item.image,
+        "https://img.amiami.com/images/product/thumb300/201/FIGURE-054261.jpg"
+    );
+    assert_eq!(item.term_en, "Prinz Eugen APEX 1/7");
 }
@@ -1,20 +1,21 @@
 use std::error::Error;
 
-use crate::http::get_client;
+use crate::configuration::Config;
+use crate::http::Client;
 
 mod base;
 mod info;
 
 #[derive(Clone)]
 pub(crate) struct AmiAmi {
-    client: reqwest::blocking::Client,
+    client: Client,
 }
 
 impl AmiAmi {
     /// create new instance of AmiAmi
-    pub fn new() -> Result<Self, Box<dyn Error>> {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
         Ok(AmiAmi {
-            client: get_client()?,
+            client: Client::new(&config.connection)?,
         })
     }
 
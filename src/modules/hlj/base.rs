@@ -0,0 +1,196 @@
+use std::error::Error;
+
+use chrono::NaiveDate;
+use kuchiki::traits::TendrilSink;
+use kuchiki::NodeRef;
+
+use crate::currency::guesser::CurrencyGuesser;
+use crate::currency::SupportedCurrency;
+use crate::database::items::{Item, ItemConditions};
+use crate::database::listings::Listing;
+use crate::database::prices::{Availability, Price};
+use crate::modules::hlj::HobbyLinkJapan;
+use crate::modules::BaseModule;
+
+impl HobbyLinkJapan {
+    /// retrieve the URLs of the product pages from the search results
+    fn search(&self, item: &Item) -> Result<Vec<String>, Box<dyn Error>> {
+        let search_url = format!("https://www.hlj.com/search/?q={}", item.jan);
+        let res = self.client.get(search_url.as_str()).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        let product_urls = doc
+            .select("p.product-item-name a[href]")
+            .unwrap()
+            .filter_map(|link| {
+                link.attributes
+                    .borrow()
+                    .get("href")
+                    .map(|href| format!("https://www.hlj.com{}", href))
+            })
+            .collect();
+
+        Ok(product_urls)
+    }
+
+    /// retrieve the value of the product detail with the passed label (f.e. "JAN Code")
+    fn get_detail(doc: &NodeRef, label: &str) -> Option<String> {
+        let prefix = format!("{}:", label);
+        doc.select("div.product-details ul li")
+            .unwrap()
+            .map(|detail| detail.text_contents().trim().to_string())
+            .find(|detail| detail.starts_with(&prefix))
+            .map(|detail| detail[prefix.len()..].trim().to_string())
+    }
+
+    /// retrieve the availability and if the item can be ordered from the stock status,
+    /// items with an order stop can't be ordered anymore until they get restocked
+    fn get_availability(stock_status: &str) -> Option<(Availability, bool)> {
+        match stock_status.trim() {
+            "In Stock" | "Low Stock" => Some((Availability::InStock, true)),
+            "Future Release" => Some((Availability::PreOrder, true)),
+            "Backordered" => Some((Availability::Backorder, true)),
+            "Order Stop" | "Sold Out" | "Discontinued" => Some((Availability::InStock, false)),
+            _ => None,
+        }
+    }
+
+    /// retrieve the release month from the release date of the product details (f.e. "May 2024")
+    fn get_release_month(release_date: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&format!("1 {}", release_date.trim()), "%d %B %Y").ok()
+    }
+
+    /// retrieve the listing of the product page, products of other JAN numbers are skipped
+    fn get_product_listing(
+        &self,
+        item: &Item,
+        product_url: &str,
+    ) -> Result<Option<Listing>, Box<dyn Error>> {
+        let res = self.client.get(product_url).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        let jan = HobbyLinkJapan::get_detail(&doc, "JAN Code").unwrap_or_default();
+        if jan != item.jan.to_string() {
+            debug!(
+                "[{}] - skipping product {} with JAN {:?}",
+                HobbyLinkJapan::get_module_key(),
+                product_url,
+                jan
+            );
+            return Ok(None);
+        }
+
+        let stock_status = match doc.select_first("div.product-info-main p.product-stock") {
+            Ok(stock_status) => stock_status.text_contents(),
+            Err(_) => return Ok(None),
+        };
+        let (availability, in_stock) = match HobbyLinkJapan::get_availability(&stock_status) {
+            Some(availability) => availability,
+            None => {
+                warn!("unknown stock status: {}", stock_status.trim());
+                return Ok(None);
+            }
+        };
+        let release_month = HobbyLinkJapan::get_detail(&doc, "Release Date")
+            .and_then(|release_date| HobbyLinkJapan::get_release_month(&release_date));
+
+        let price_text = match doc.select_first("div.product-info-price span.price") {
+            Ok(price) => price.text_contents(),
+            Err(_) => return Ok(None),
+        };
+        let price = match CurrencyGuesser::get_currency_value(price_text, SupportedCurrency::JPY) {
+            Ok(price) => price,
+            Err(err) => {
+                warn!(
+                    "[{}] - unable to parse the price of {} (err: {:?})",
+                    HobbyLinkJapan::get_module_key(),
+                    product_url,
+                    err
+                );
+                return Ok(None);
+            }
+        };
+
+        // HLJ only sells new items
+        let mut listing = Listing::new(
+            Price::new(
+                item.clone(),
+                price,
+                product_url.to_string(),
+                HobbyLinkJapan::get_module_key(),
+                ItemConditions::New,
+            )
+            .with_availability(availability, release_month),
+            "".to_string(),
+            "".to_string(),
+        );
+        listing.in_stock = in_stock;
+
+        Ok(Some(listing))
+    }
+}
+
+impl BaseModule for HobbyLinkJapan {
+    /// retrieve the module key
+    fn get_module_key(&self) -> String {
+        HobbyLinkJapan::get_module_key()
+    }
+
+    /// retrieve the listings of all products matching the JAN
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+        for product_url in self.search(item)? {
+            if let Some(listing) = self.get_product_listing(item, &product_url)? {
+                listings.push(listing);
+            }
+        }
+
+        Ok(listings)
+    }
+}
+
+#[test]
+pub fn test_get_lowest_prices() {
+    use crate::currency::money::Money;
+    use crate::http::Client;
+    use crate::modules::Module;
+
+    let item = &mut Item {
+        id: 0,
+        jan: 4_580_590_174_569,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+
+    let hlj = HobbyLinkJapan {
+        client: Client::from_fixture("hlj/lowest_prices"),
+    };
+
+    // products of other JAN numbers are skipped, the release date is known for all products
+    let listings = hlj.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 2);
+    assert_eq!(listings[0].price.condition, ItemConditions::New);
+    assert_eq!(listings[0].price.availability, Availability::PreOrder);
+    assert_eq!(
+        listings[0].price.release_month,
+        NaiveDate::from_ymd_opt(2024, 5, 1)
+    );
+    assert_eq!(listings[1].price.availability, Availability::InStock);
+    assert_eq!(
+        listings[1].price.release_month,
+        NaiveDate::from_ymd_opt(2022, 11, 1)
+    );
+
+    let prices = hlj.get_lowest_prices(item).unwrap();
+    assert!(prices.used.is_none());
+
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(4800, SupportedCurrency::JPY));
+    assert_eq!(
+        new.url,
+        "https://www.hlj.com/nendoroid-saber-alter-gsc12344"
+    );
+}
@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use crate::configuration::Config;
+use crate::http::Client;
+
+mod base;
+
+#[derive(Clone)]
+pub(crate) struct HobbyLinkJapan {
+    client: Client,
+}
+
+impl HobbyLinkJapan {
+    /// create new instance of HobbyLinkJapan
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        Ok(HobbyLinkJapan {
+            client: Client::new(&config.connection)?,
+        })
+    }
+
+    pub fn get_module_key() -> String {
+        "hlj.com".to_string()
+    }
+}
@@ -0,0 +1,216 @@
+use std::error::Error;
+
+use chrono::NaiveDate;
+use kuchiki::traits::TendrilSink;
+use kuchiki::NodeRef;
+use regex::Regex;
+
+use crate::currency::guesser::CurrencyGuesser;
+use crate::currency::SupportedCurrency;
+use crate::database::items::{Item, ItemConditions};
+use crate::database::listings::Listing;
+use crate::database::prices::{Availability, Price};
+use crate::modules::hobbysearch::HobbySearch;
+use crate::modules::BaseModule;
+
+impl HobbySearch {
+    /// retrieve the URLs of the product pages from the search results
+    fn search(&self, item: &Item) -> Result<Vec<String>, Box<dyn Error>> {
+        let search_url = format!(
+            "https://www.1999.co.jp/search?typ1_c=101&searchkey={}",
+            item.jan
+        );
+        let res = self.client.get(search_url.as_str()).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        let product_urls = doc
+            .select("div.itemList div.itemBlock p.itemName a[href]")
+            .unwrap()
+            .filter_map(|link| {
+                link.attributes
+                    .borrow()
+                    .get("href")
+                    .map(|href| format!("https://www.1999.co.jp{}", href))
+            })
+            .collect();
+
+        Ok(product_urls)
+    }
+
+    /// retrieve the value of the item specification with the passed header (f.e. "JANコード")
+    fn get_spec(doc: &NodeRef, header: &str) -> Option<String> {
+        for row in doc.select("table.tbl_item_spec tr").unwrap() {
+            let row = row.as_node();
+            let matches_header = row
+                .select_first("th")
+                .map(|th| th.text_contents().trim() == header)
+                .unwrap_or(false);
+            if matches_header {
+                return row
+                    .select_first("td")
+                    .ok()
+                    .map(|td| td.text_contents().trim().to_string());
+            }
+        }
+
+        None
+    }
+
+    /// retrieve the availability and if the item can be ordered from the stock status
+    fn get_availability(stock_status: &str) -> Option<(Availability, bool)> {
+        match stock_status.trim() {
+            "在庫あり" | "残りわずか" => Some((Availability::InStock, true)),
+            "予約受付中" => Some((Availability::PreOrder, true)),
+            "お取り寄せ" => Some((Availability::Backorder, true)),
+            "予約受付終了" | "品切れ" | "販売終了" => {
+                Some((Availability::InStock, false))
+            }
+            _ => None,
+        }
+    }
+
+    /// retrieve the release month from the release date of the specification
+    /// (f.e. "2024年05月" or "2024年05月下旬")
+    fn get_release_month(release_date: &str) -> Option<NaiveDate> {
+        let captures = Regex::new(r"(?P<year>\d{4})年(?P<month>\d{1,2})月")
+            .unwrap()
+            .captures(release_date)?;
+        NaiveDate::from_ymd_opt(
+            captures["year"].parse().ok()?,
+            captures["month"].parse().ok()?,
+            1,
+        )
+    }
+
+    /// retrieve the listing of the product page, products of other JAN numbers are skipped
+    fn get_product_listing(
+        &self,
+        item: &Item,
+        product_url: &str,
+    ) -> Result<Option<Listing>, Box<dyn Error>> {
+        let res = self.client.get(product_url).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        let jan = HobbySearch::get_spec(&doc, "JANコード").unwrap_or_default();
+        if jan != item.jan.to_string() {
+            debug!(
+                "[{}] - skipping product {} with JAN {:?}",
+                HobbySearch::get_module_key(),
+                product_url,
+                jan
+            );
+            return Ok(None);
+        }
+
+        let stock_status = match doc.select_first("div.itemDetail p.itemStatus") {
+            Ok(stock_status) => stock_status.text_contents(),
+            Err(_) => return Ok(None),
+        };
+        let (availability, in_stock) = match HobbySearch::get_availability(&stock_status) {
+            Some(availability) => availability,
+            None => {
+                warn!("unknown stock status: {}", stock_status.trim());
+                return Ok(None);
+            }
+        };
+        let release_month = HobbySearch::get_spec(&doc, "発売日")
+            .and_then(|release_date| HobbySearch::get_release_month(&release_date));
+
+        // the displayed price already includes the consumption tax
+        let price_text = match doc.select_first("div.itemDetail span.price") {
+            Ok(price) => price.text_contents(),
+            Err(_) => return Ok(None),
+        };
+        let price = match CurrencyGuesser::get_currency_value(price_text, SupportedCurrency::JPY) {
+            Ok(price) => price,
+            Err(err) => {
+                warn!(
+                    "[{}] - unable to parse the price of {} (err: {:?})",
+                    HobbySearch::get_module_key(),
+                    product_url,
+                    err
+                );
+                return Ok(None);
+            }
+        };
+
+        // the new items category is searched, used items are sold on a separate site
+        let mut listing = Listing::new(
+            Price::new(
+                item.clone(),
+                price,
+                product_url.to_string(),
+                HobbySearch::get_module_key(),
+                ItemConditions::New,
+            )
+            .with_availability(availability, release_month),
+            "".to_string(),
+            "".to_string(),
+        );
+        listing.in_stock = in_stock;
+
+        Ok(Some(listing))
+    }
+}
+
+impl BaseModule for HobbySearch {
+    /// retrieve the module key
+    fn get_module_key(&self) -> String {
+        HobbySearch::get_module_key()
+    }
+
+    /// retrieve the listings of all products matching the JAN
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+        for product_url in self.search(item)? {
+            if let Some(listing) = self.get_product_listing(item, &product_url)? {
+                listings.push(listing);
+            }
+        }
+
+        Ok(listings)
+    }
+}
+
+#[test]
+pub fn test_get_lowest_prices() {
+    use crate::currency::money::Money;
+    use crate::http::Client;
+    use crate::modules::Module;
+
+    let item = &mut Item {
+        id: 0,
+        jan: 4_580_590_174_569,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+
+    let hobbysearch = HobbySearch {
+        client: Client::from_fixture("hobbysearch/lowest_prices"),
+    };
+
+    // the reissue of the figure is listed as separate product with the same JAN
+    let listings = hobbysearch.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 2);
+    assert!(!listings[0].in_stock);
+    assert_eq!(
+        listings[0].price.release_month,
+        NaiveDate::from_ymd_opt(2022, 11, 1)
+    );
+    assert_eq!(listings[1].price.availability, Availability::PreOrder);
+    assert_eq!(
+        listings[1].price.release_month,
+        NaiveDate::from_ymd_opt(2024, 5, 1)
+    );
+
+    // products which can't be ordered anymore are ignored
+    let prices = hobbysearch.get_lowest_prices(item).unwrap();
+    assert!(prices.used.is_none());
+
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(4620, SupportedCurrency::JPY));
+    assert_eq!(new.url, "https://www.1999.co.jp/10987654");
+}
@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use crate::configuration::Config;
+use crate::http::Client;
+
+mod base;
+
+#[derive(Clone)]
+pub(crate) struct HobbySearch {
+    client: Client,
+}
+
+impl HobbySearch {
+    /// create new instance of HobbySearch
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        Ok(HobbySearch {
+            client: Client::new(&config.connection)?,
+        })
+    }
+
+    pub fn get_module_key() -> String {
+        "1999.co.jp".to_string()
+    }
+}
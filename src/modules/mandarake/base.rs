@@ -0,0 +1,174 @@
+use std::error::Error;
+
+use kuchiki::traits::TendrilSink;
+use kuchiki::NodeRef;
+use reqwest::Url;
+
+use crate::currency::guesser::CurrencyGuesser;
+use crate::currency::SupportedCurrency;
+use crate::database::items::{Item, ItemConditions};
+use crate::database::listings::Listing;
+use crate::database::prices::{Availability, Price};
+use crate::modules::mandarake::Mandarake;
+use crate::modules::{get_grades_from_title, BaseModule};
+
+impl Mandarake {
+    /// retrieve the listings of the search results for the passed keyword
+    fn search(&self, item: &Item, keyword: &str) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+
+        let search_url = Url::parse_with_params(
+            "https://order.mandarake.co.jp/order/listPage/list",
+            &[("keyword", keyword)],
+        )?;
+        let res = self.client.get(search_url.as_str()).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        for css_match in doc.select("div.entry div.block").unwrap() {
+            let block = css_match.as_node();
+            // skip blocks which can't be parsed instead of failing the whole check
+            let title_link = match block.select_first("div.title a[href]") {
+                Ok(title_link) => title_link,
+                Err(_) => continue,
+            };
+            let title = title_link.text_contents().trim().to_string();
+            let sale_url = match title_link.attributes.borrow().get("href") {
+                Some(href) => format!("https://order.mandarake.co.jp{}", href),
+                None => continue,
+            };
+
+            let price_text = match block.select_first("div.price p") {
+                Ok(price) => price.text_contents(),
+                Err(_) => continue,
+            };
+            let price =
+                match CurrencyGuesser::get_currency_value(price_text, SupportedCurrency::JPY) {
+                    Ok(price) => price,
+                    Err(err) => {
+                        warn!(
+                            "[{}] - unable to parse the price of {} (err: {:?})",
+                            Mandarake::get_module_key(),
+                            sale_url,
+                            err
+                        );
+                        continue;
+                    }
+                };
+            // the store branch which has the item in stock
+            let seller = match block.select_first("div.basicinfo div.shop p") {
+                Ok(shop) => shop.text_contents().trim().to_string(),
+                Err(_) => "".to_string(),
+            };
+
+            // nearly everything is secondhand, only unopened items are new
+            let cond = if title.contains("未開封") {
+                ItemConditions::New
+            } else {
+                ItemConditions::Used
+            };
+            let (item_grade, box_grade) = get_grades_from_title(&title);
+
+            let mut listing = Listing::new(
+                Price::new(
+                    item.clone(),
+                    price,
+                    sale_url,
+                    Mandarake::get_module_key(),
+                    cond,
+                )
+                .with_grades(item_grade, box_grade)
+                .with_availability(Mandarake::get_availability(block), None),
+                seller,
+                "".to_string(),
+            );
+            listing.in_stock = block.select_first("div.soldout").is_err();
+            listings.push(listing);
+        }
+
+        Ok(listings)
+    }
+
+    /// items which are only in stock at the store (店頭) have to be reserved from the branch
+    /// before they can be shipped and can get sold in the store in the meantime
+    fn get_availability(block: &NodeRef) -> Availability {
+        match block.select_first("div.stock p") {
+            Ok(stock) if stock.text_contents().contains("店頭") => Availability::Backorder,
+            _ => Availability::InStock,
+        }
+    }
+}
+
+impl BaseModule for Mandarake {
+    /// retrieve the module key
+    fn get_module_key(&self) -> String {
+        Mandarake::get_module_key()
+    }
+
+    /// retrieve the listings of the search by the JAN and by the japanese term of the item,
+    /// most listings of secondhand items don't include the JAN
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = self.search(item, &item.jan.to_string())?;
+
+        if !item.term_jp.is_empty() {
+            for listing in self.search(item, &item.term_jp)? {
+                if !listings
+                    .iter()
+                    .any(|known_listing| known_listing.price.url == listing.price.url)
+                {
+                    listings.push(listing);
+                }
+            }
+        }
+
+        Ok(listings)
+    }
+}
+
+#[test]
+pub fn test_get_lowest_prices() {
+    use crate::currency::money::Money;
+    use crate::database::items::Grade;
+    use crate::http::Client;
+    use crate::modules::Module;
+
+    let item = &mut Item {
+        id: 0,
+        jan: 4_545_784_042_649,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "ねんどろいど セイバー".to_string(),
+        disabled: false,
+    };
+
+    let mandarake = Mandarake {
+        client: Client::from_fixture("mandarake/lowest_prices"),
+    };
+
+    // listings found by the JAN and the japanese term are only added once
+    let listings = mandarake.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 4);
+    assert_eq!(listings[0].seller, "中野店");
+    assert_eq!(listings[0].price.box_grade, Some(Grade::C));
+    assert_eq!(listings[1].price.condition, ItemConditions::New);
+    assert_eq!(listings[1].price.availability, Availability::Backorder);
+    assert!(!listings[2].in_stock);
+    assert_eq!(listings[3].seller, "福岡店");
+
+    let prices = mandarake.get_lowest_prices(item).unwrap();
+
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(9800, SupportedCurrency::JPY));
+    assert_eq!(
+        new.url,
+        "https://order.mandarake.co.jp/order/detailPage/item?itemCode=1142217402"
+    );
+
+    // sold out listings are ignored for the lowest prices
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(6500, SupportedCurrency::JPY));
+    assert_eq!(
+        used.url,
+        "https://order.mandarake.co.jp/order/detailPage/item?itemCode=1142217401"
+    );
+}
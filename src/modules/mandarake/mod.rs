@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use crate::configuration::Config;
+use crate::http::Client;
+
+mod base;
+
+#[derive(Clone)]
+pub(crate) struct Mandarake {
+    client: Client,
+}
+
+impl Mandarake {
+    /// create new instance of Mandarake
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        Ok(Mandarake {
+            client: Client::new(&config.connection)?,
+        })
+    }
+
+    pub fn get_module_key() -> String {
+        "mandarake.co.jp".to_string()
+    }
+}
@@ -0,0 +1,219 @@
+use std::error::Error;
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::currency::money::Money;
+use crate::currency::SupportedCurrency;
+use crate::database::items::{Grade, Item, ItemConditions};
+use crate::database::listings::Listing;
+use crate::database::prices::Price;
+use crate::modules::mercari::Mercari;
+use crate::modules::{get_grades_from_title, BaseModule};
+
+/// the search request for the search API of the Mercari web application
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiSearchRequest {
+    page_size: u32,
+    search_condition: ApiSearchCondition,
+    default_datasets: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiSearchCondition {
+    keyword: String,
+    status: Vec<String>,
+    sort: String,
+    order: String,
+}
+
+/// the search response returned from the search API of Mercari
+#[derive(Deserialize)]
+struct ApiSearchResponse {
+    items: Vec<ApiSearchItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiSearchItem {
+    id: String,
+    name: String,
+    /// the price in yen, the API returns all numbers as strings
+    price: String,
+    status: String,
+    item_condition_id: String,
+    seller_id: String,
+    /// unix timestamp of the last update, for sold items the time of the sale
+    updated: String,
+}
+
+impl ApiSearchItem {
+    /// retrieve the URL of the item page
+    fn get_url(&self) -> String {
+        format!("https://jp.mercari.com/item/{}", self.id)
+    }
+}
+
+impl Mercari {
+    /// map the condition scale of Mercari to the condition and the grade of the item:
+    /// 1 新品、未使用, 2 未使用に近い, 3 目立った傷や汚れなし, 4 やや傷や汚れあり,
+    /// 5 傷や汚れあり, 6 全体的に状態が悪い
+    fn get_condition(condition_id: &str) -> Option<(ItemConditions, Grade)> {
+        match condition_id {
+            "1" => Some((ItemConditions::New, Grade::S)),
+            "2" => Some((ItemConditions::Used, Grade::A)),
+            "3" => Some((ItemConditions::Used, Grade::B)),
+            "4" | "5" => Some((ItemConditions::Used, Grade::C)),
+            "6" => Some((ItemConditions::Used, Grade::Junk)),
+            _ => None,
+        }
+    }
+
+    /// retrieve the items on sale and the sold items of the search results for the passed keyword
+    fn search(&self, keyword: &str) -> Result<Vec<ApiSearchItem>, Box<dyn Error>> {
+        let request = ApiSearchRequest {
+            page_size: 120,
+            search_condition: ApiSearchCondition {
+                keyword: keyword.to_string(),
+                status: vec!["STATUS_ON_SALE".to_string(), "STATUS_SOLD_OUT".to_string()],
+                sort: "SORT_CREATED_TIME".to_string(),
+                order: "ORDER_DESC".to_string(),
+            },
+            default_datasets: vec!["DATASET_TYPE_MERCARI".to_string()],
+        };
+
+        let res = self
+            .client
+            .post("https://api.mercari.jp/v2/entities:search")
+            .header("Content-Type", "application/json")
+            .header("X-Platform", "web")
+            .body(serde_json::to_string(&request)?)
+            .send()?;
+        let search_response: ApiSearchResponse = serde_json::from_str(&res.text()?)?;
+
+        Ok(search_response.items)
+    }
+}
+
+impl BaseModule for Mercari {
+    /// retrieve the module key
+    fn get_module_key(&self) -> String {
+        Mercari::get_module_key()
+    }
+
+    /// retrieve the listings of the search by the japanese term of the item since the listings
+    /// of private sellers rarely include the JAN, sold items are returned as sold listings
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+        if item.term_jp.is_empty() {
+            debug!(
+                "[{}] - no japanese term for {:?}, skipping search",
+                Mercari::get_module_key(),
+                item.description
+            );
+            return Ok(listings);
+        }
+
+        for search_item in self.search(&item.term_jp)? {
+            // items in trading are already bought and only waiting for the transaction to complete
+            let sold = match search_item.status.as_str() {
+                "ITEM_STATUS_ON_SALE" => false,
+                "ITEM_STATUS_TRADING" | "ITEM_STATUS_SOLD_OUT" => true,
+                _ => continue,
+            };
+            let (cond, condition_grade) =
+                match Mercari::get_condition(&search_item.item_condition_id) {
+                    Some(condition) => condition,
+                    None => {
+                        warn!("unknown condition: {}", search_item.item_condition_id);
+                        continue;
+                    }
+                };
+            let price = match search_item.price.parse::<i64>() {
+                Ok(price) => price,
+                Err(_) => continue,
+            };
+
+            // flaws mentioned in the title are more specific than the condition scale
+            let (item_grade, box_grade) = get_grades_from_title(&search_item.name);
+
+            let mut listing = Listing::new(
+                Price::new(
+                    item.clone(),
+                    Money::new(price, SupportedCurrency::JPY),
+                    search_item.get_url(),
+                    Mercari::get_module_key(),
+                    cond,
+                )
+                .with_grades(item_grade.or(Some(condition_grade)), box_grade),
+                search_item.seller_id,
+                "".to_string(),
+            );
+            if sold {
+                listing.in_stock = false;
+                listing.sold = true;
+                listing.sold_out_at = search_item
+                    .updated
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|updated| Utc.timestamp_opt(updated, 0).single());
+            }
+            listings.push(listing);
+        }
+
+        Ok(listings)
+    }
+}
+
+#[test]
+pub fn test_get_lowest_prices() {
+    use crate::http::Client;
+    use crate::modules::Module;
+
+    let item = &mut Item {
+        id: 0,
+        jan: 4_545_784_042_649,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "ねんどろいど セイバー".to_string(),
+        disabled: false,
+    };
+
+    let mercari = Mercari {
+        client: Client::from_fixture("mercari/lowest_prices"),
+    };
+
+    // items of unknown status are skipped, sold items are marked as sold listings
+    let listings = mercari.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 5);
+    assert_eq!(listings[0].seller, "123456789");
+    assert_eq!(listings[0].price.condition, ItemConditions::New);
+    assert_eq!(listings[0].price.item_grade, Some(Grade::S));
+    assert_eq!(listings[1].price.item_grade, Some(Grade::B));
+    assert_eq!(listings[1].price.box_grade, Some(Grade::Junk));
+    assert!(listings[2].sold);
+    assert_eq!(
+        listings[2].sold_out_at,
+        Some(Utc.timestamp_opt(1_714_471_200, 0).unwrap())
+    );
+    assert!(listings[3].sold);
+    assert_eq!(listings[4].price.item_grade, Some(Grade::Junk));
+
+    // the realized prices of sold items are ignored for the lowest prices
+    let prices = mercari.get_lowest_prices(item).unwrap();
+    assert_eq!(
+        prices.new.unwrap().price,
+        Money::new(9500, SupportedCurrency::JPY)
+    );
+
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(3000, SupportedCurrency::JPY));
+    assert_eq!(used.url, "https://jp.mercari.com/item/m10000000005");
+
+    // items can't be found without the japanese term
+    item.term_jp = "".to_string();
+    assert!(mercari.get_listings(item).unwrap().is_empty());
+}
@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use crate::configuration::Config;
+use crate::http::Client;
+
+mod base;
+
+#[derive(Clone)]
+pub(crate) struct Mercari {
+    client: Client,
+}
+
+impl Mercari {
+    /// create new instance of Mercari
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        Ok(Mercari {
+            client: Client::new(&config.connection)?,
+        })
+    }
+
+    pub fn get_module_key() -> String {
+        "jp.mercari.com".to_string()
+    }
+}
@@ -2,25 +2,41 @@ use core::fmt;
 use std::error::Error;
 use std::sync::{Arc, Barrier, Mutex};
 
+use chrono::{DateTime, Utc};
 use std::fmt::Formatter;
 use threadpool::ThreadPool;
 
-use crate::configuration::Configuration;
+use crate::configuration::Config;
 use crate::currency::conversion::CurrencyConversion;
-use crate::currency::guesser::CurrencyGuesser;
-use crate::database::items::Item;
+use crate::database::items::{Grade, Item, ItemConditions};
+use crate::database::listings::Listing;
 use crate::database::prices::Price;
+use crate::database::sales::Sale;
+use crate::database::Database;
+use crate::http::Client;
 use crate::modules::amazon::AmazonCoJp;
 use crate::modules::amiami::AmiAmi;
+use crate::modules::hlj::HobbyLinkJapan;
+use crate::modules::hobbysearch::HobbySearch;
+use crate::modules::mandarake::Mandarake;
+use crate::modules::mercari::Mercari;
 use crate::modules::myfigurecollection::MyFigureCollection;
 use crate::modules::rakuten::Rakuten;
 use crate::modules::solarisjapan::SolarisJapan;
+use crate::modules::surugaya::SurugaYa;
+use crate::modules::yahoo_auctions::YahooAuctions;
 
 pub(crate) mod amazon;
 pub(crate) mod amiami;
+pub(crate) mod hlj;
+pub(crate) mod hobbysearch;
+pub(crate) mod mandarake;
+pub(crate) mod mercari;
 pub(crate) mod myfigurecollection;
 pub(crate) mod rakuten;
 pub(crate) mod solarisjapan;
+pub(crate) mod surugaya;
+pub(crate) mod yahoo_auctions;
 
 /// Prices is a simple struct for prices including an option for new and used conditions
 struct Prices {
@@ -28,6 +44,88 @@ struct Prices {
     new: Option<Price>,
 }
 
+impl Prices {
+    /// derive the lowest prices for new and used condition from the passed listings,
+    /// listings which are out of stock are ignored
+    ///
+    /// the current bid of an auction isn't a price the item can be bought for,
+    /// so auctions are compared by their buyout price and auctions without one are ignored
+    fn from_listings(listings: &[Listing]) -> Self {
+        let mut prices = Prices {
+            used: None,
+            new: None,
+        };
+
+        for listing in listings.iter().filter(|listing| listing.in_stock) {
+            let lowest_price = match listing.price.condition {
+                ItemConditions::New => &mut prices.new,
+                ItemConditions::Used => &mut prices.used,
+                ItemConditions::All => continue,
+            };
+
+            let price = if listing.price.is_auction() {
+                match listing.price.get_buyout_price() {
+                    Some(buyout_price) => buyout_price,
+                    None => continue,
+                }
+            } else {
+                listing.price.clone()
+            };
+
+            if lowest_price
+                .as_ref()
+                .map_or(true, |lowest_price| is_lower_price(&price, lowest_price))
+            {
+                *lowest_price = Some(price);
+            }
+        }
+
+        prices
+    }
+}
+
+/// check if the price is lower than the other price, converted prices are compared by their total
+/// since the listings of a module can be in different currencies
+fn is_lower_price(price: &Price, other: &Price) -> bool {
+    if price.converted_price.amount > 0 && other.converted_price.amount > 0 {
+        price.get_converted_total() < other.get_converted_total()
+    } else {
+        price.price < other.price
+    }
+}
+
+/// derive the grades of the item and its box from common keywords of japanese listing titles,
+/// shops without a grading of their own only mention the flaws in the title
+fn get_grades_from_title(title: &str) -> (Option<Grade>, Option<Grade>) {
+    let item_grade = if title.contains("ジャンク") {
+        Some(Grade::Junk)
+    } else if title.contains("未開封") {
+        Some(Grade::S)
+    } else {
+        None
+    };
+
+    let box_grade = if title.contains("箱なし") || title.contains("箱無し") {
+        Some(Grade::Junk)
+    } else if title.contains("箱傷み") || title.contains("箱潰れ") || title.contains("箱破損")
+    {
+        Some(Grade::C)
+    } else {
+        None
+    };
+
+    (item_grade, box_grade)
+}
+
+/// ItemCheck contains the listings and sales of an item check
+/// and the modules which were checked successfully
+#[derive(Clone, Default)]
+pub(crate) struct ItemCheck {
+    pub(crate) listings: Vec<Listing>,
+    pub(crate) sales: Vec<Sale>,
+    pub(crate) modules: Vec<String>,
+}
+
 /// generic error type to be returned from modules for if the figure couldn't be found
 #[derive(Debug)]
 struct NotFoundError {}
@@ -59,7 +157,9 @@ impl std::fmt::Display for NoInfoFromModulesError {
 /// mark as error implementation
 impl Error for NoInfoFromModulesError {}
 
-/// Module contains the public functionality you can use from the module pool
+/// Module contains the lowest prices of a single module, the module pool derives the lowest prices
+/// from the listings of all modules so this is only used by the module tests
+#[cfg(test)]
 trait Module {
     fn get_lowest_prices(&self, item: &Item) -> Result<Prices, Box<dyn Error>>;
 }
@@ -67,7 +167,7 @@ trait Module {
 /// BaseModule contains all the functionality required from the implemented modules
 trait BaseModule: BaseModuleClone {
     fn get_module_key(&self) -> String;
-    fn get_lowest_prices(&self, item: &Item) -> Result<Prices, Box<dyn Error>>;
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>>;
 }
 
 /// InfoModule are special modules with the additional functionality to update the item details
@@ -76,14 +176,15 @@ trait InfoModule {
     fn update_figure_details(&self, item: &mut Item) -> Result<(), Box<dyn Error>>;
 }
 
+#[cfg(test)]
 impl<T> Module for T
 where
     T: BaseModule,
 {
-    /// retrieve the lowest prices for new and used items
+    /// retrieve the lowest prices for new and used items derived from the listings
     fn get_lowest_prices(&self, item: &Item) -> Result<Prices, Box<dyn Error>> {
         debug!("checking prices from module: {:?}", self.get_module_key());
-        self.get_lowest_prices(item)
+        Ok(Prices::from_listings(&self.get_listings(item)?))
     }
 }
 
@@ -114,34 +215,64 @@ pub(crate) struct ModulePool {
     modules: Vec<Box<dyn BaseModule + Send + Sync>>,
     info_modules: Vec<Box<dyn InfoModule + Send + Sync>>,
     conversion: CurrencyConversion,
+    config: Config,
+    /// the time the exchange rates of the conversion were retrieved
+    created_at: DateTime<Utc>,
 }
 
 /// implementation of the module pool
 impl ModulePool {
     /// returns the module pool with all the implemented modules
-    pub fn new() -> Result<Self, Box<dyn Error>> {
-        let conversion = CurrencyConversion::new()?;
+    pub fn new(config: &Config, db: &Database) -> Result<Self, Box<dyn Error>> {
+        let conversion =
+            CurrencyConversion::new(&config.conversion, &Client::new(&config.connection)?, db)?;
+        let modules: Vec<Box<dyn BaseModule + Send + Sync>> = vec![
+            Box::from(MyFigureCollection::new(conversion.clone(), config)?),
+            Box::from(AmiAmi::new(config)?),
+            Box::from(SolarisJapan::new(config)?),
+            Box::from(AmazonCoJp::new(config)?),
+            Box::from(Rakuten::new(config)?),
+            Box::from(Mandarake::new(config)?),
+            Box::from(SurugaYa::new(config)?),
+            Box::from(YahooAuctions::new(config)?),
+            Box::from(Mercari::new(config)?),
+            Box::from(HobbyLinkJapan::new(config)?),
+            Box::from(HobbySearch::new(config)?),
+        ];
+
+        for module_key in config.conversion.modules.keys() {
+            if !modules
+                .iter()
+                .any(|module| &module.get_module_key() == module_key)
+            {
+                warn!("cost profile for unknown module: {}", module_key);
+            }
+        }
+
         Ok(ModulePool {
-            modules: vec![
-                Box::from(MyFigureCollection::new(conversion.clone())?),
-                Box::from(AmiAmi::new()?),
-                Box::from(SolarisJapan::new()?),
-                Box::from(AmazonCoJp::new()?),
-                Box::from(Rakuten::new()?),
-            ],
+            modules,
             info_modules: vec![
-                Box::from(MyFigureCollection::new(conversion.clone())?),
-                Box::from(AmiAmi::new()?),
+                Box::from(MyFigureCollection::new(conversion.clone(), config)?),
+                Box::from(AmiAmi::new(config)?),
             ],
             conversion,
+            config: config.clone(),
+            created_at: Utc::now(),
         })
     }
 
-    /// checks all modules for the prices of the passed item
-    pub fn check_item(&self, item: Item) -> Vec<Price> {
-        let collected_prices: Arc<Mutex<Vec<Price>>> = Arc::new(Mutex::new(vec![]));
+    /// check if the exchange rates of the module pool are older than the configured TTL
+    /// and the module pool should be recreated with the current exchange rates
+    pub fn has_expired_rates(&self) -> bool {
+        self.created_at + self.config.conversion.get_rates_ttl() <= Utc::now()
+    }
+
+    /// checks all modules for the listings of the passed item, sold listings are collected as sales
+    /// and listings which can't be converted to the used currency are skipped
+    pub fn check_item(&self, item: Item) -> ItemCheck {
+        let item_check: Arc<Mutex<ItemCheck>> = Arc::new(Mutex::new(ItemCheck::default()));
 
-        let used_currency = Configuration::get_used_currency();
+        let used_currency = self.config.general.currency.clone();
         let pool = ThreadPool::new(self.modules.len());
         let barrier = Arc::new(Barrier::new(self.modules.len() + 1));
 
@@ -149,39 +280,57 @@ impl ModulePool {
             let barrier = barrier.clone();
             let item = item.clone();
             let module = self.modules[i].clone();
-            let collected_prices = collected_prices.clone();
+            let item_check = item_check.clone();
             let conversion = self.conversion.clone();
+            let conversion_config = self.config.conversion.clone();
             let used_currency = used_currency.clone();
 
             pool.execute(move || {
-                match module.get_lowest_prices(&item) {
-                    Ok(prices) => {
-                        for price_option in vec![prices.new, prices.used] {
-                            if let Some(mut price) = price_option {
-                                if let Some(currency) = CurrencyGuesser::new()
-                                    .guess_currency(price.currency.as_str().to_string())
-                                {
-                                    price.converted_price = conversion.convert_price_to(
-                                        price.price,
-                                        currency.clone(),
-                                        used_currency.clone(),
+                match module.get_listings(&item) {
+                    Ok(listings) => {
+                        debug!(
+                            "[{}] - found {} listings for {:?}",
+                            module.get_module_key(),
+                            listings.len(),
+                            item.description
+                        );
+
+                        for mut listing in listings {
+                            let price = &mut listing.price;
+                            match conversion.convert_price_to(&price.price, used_currency.clone()) {
+                                Ok(converted_price) => price.set_conversion(
+                                    converted_price,
+                                    &conversion_config.get_listing_costs(
+                                        &price.module,
+                                        &price.price.currency,
+                                        item.jan,
+                                    ),
+                                ),
+                                Err(err) => {
+                                    // unconverted prices can't be compared to the conditions
+                                    warn!(
+                                        "[{}] - unable to convert price {} for {:?} (err: {:?})",
+                                        price.module, price.price, item.description, err
                                     );
-                                    price.converted_currency = used_currency.to_string();
-                                    price.shipping = Configuration::get_shipping(currency.clone());
-                                    price.taxes =
-                                        Configuration::get_used_tax_rate(currency.clone());
+                                    continue;
                                 }
-                                info!(
-                                    "[{}] - detected price for {:?}: price: {:.2} {} (without shipping/taxes: {:.2} {} / {:.2} {}), condition: {:?}",
-                                    price.module, item.description, price.get_converted_total(), price.converted_currency, price.price, price.currency, price.converted_price, price.converted_currency, price.condition,
-                                );
-
-                                // push our result into the collected prices and release them again
-                                let mut collected_prices = collected_prices.lock().unwrap();
-                                collected_prices.push(price);
-                                drop(collected_prices);
                             }
+
+                            // push our result into the collected listings and release them again
+                            let mut item_check = item_check.lock().unwrap();
+                            if listing.sold {
+                                item_check.sales.push(Sale::from_listing(listing));
+                            } else {
+                                item_check.listings.push(listing);
+                            }
+                            drop(item_check);
                         }
+
+                        item_check
+                            .lock()
+                            .unwrap()
+                            .modules
+                            .push(module.get_module_key());
                     }
                     Err(err) => warn!(
                         "[{}] - error checking for prices for {:?} (err: {:?})",
@@ -200,7 +349,37 @@ impl ModulePool {
         barrier.wait();
 
         #[allow(clippy::redundant_clone)]
-        collected_prices.clone().lock().unwrap().to_vec()
+        item_check.clone().lock().unwrap().clone()
+    }
+
+    /// derive the lowest prices for new and used condition of every module from the passed listings
+    pub fn get_lowest_prices(item: &Item, listings: &[Listing]) -> Vec<Price> {
+        let mut modules: Vec<&String> = listings
+            .iter()
+            .map(|listing| &listing.price.module)
+            .collect();
+        modules.sort();
+        modules.dedup();
+
+        let mut lowest_prices = vec![];
+        for module in modules {
+            let module_listings: Vec<Listing> = listings
+                .iter()
+                .filter(|listing| &listing.price.module == module)
+                .cloned()
+                .collect();
+
+            let prices = Prices::from_listings(&module_listings);
+            for price in vec![prices.new, prices.used].into_iter().flatten() {
+                info!(
+                    "[{}] - detected price for {:?}: price: {} (without shipping/taxes: {} / {}), condition: {:?}",
+                    price.module, item.description, price.get_converted_total(), price.price, price.converted_price, price.condition,
+                );
+                lowest_prices.push(price);
+            }
+        }
+
+        lowest_prices
     }
 
     /// iterates through the info modules and tries to update the item information
@@ -226,3 +405,88 @@ impl ModulePool {
         Err(Box::from(NoInfoFromModulesError {}))
     }
 }
+
+#[test]
+fn test_lowest_prices_auctions() {
+    use chrono::Duration;
+
+    use crate::conditions::ConditionType;
+    use crate::configuration::ListingCosts;
+    use crate::currency::money::Money;
+    use crate::currency::SupportedCurrency;
+    use crate::database::conditions::Condition;
+    use crate::database::prices::Prices as _;
+
+    let db = Database::open(":memory:").unwrap();
+    let item = Item {
+        id: 1,
+        jan: 4_580_416_940_283,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+    let get_listing = |value: i64, buyout_price: Option<i64>, url: &str| {
+        let mut price = Price::new(
+            item.clone(),
+            Money::new(value, SupportedCurrency::JPY),
+            url.to_string(),
+            "auctions.yahoo.co.jp".to_string(),
+            ItemConditions::Used,
+        );
+        price.set_conversion(
+            Money::new(value, SupportedCurrency::JPY),
+            &ListingCosts::default(),
+        );
+        let auction_end = price.timestamp + Duration::hours(12);
+        Listing::new(
+            price.with_auction(
+                buyout_price.map(|buyout_price| Money::new(buyout_price, SupportedCurrency::JPY)),
+                auction_end,
+            ),
+            "".to_string(),
+            "".to_string(),
+        )
+    };
+
+    // the current bid of an auction without buyout price is no lowest price
+    let listings = vec![get_listing(
+        1,
+        None,
+        "https://page.auctions.yahoo.co.jp/jp/auction/x1",
+    )];
+    assert!(ModulePool::get_lowest_prices(&item, &listings).is_empty());
+
+    // auctions with buyout price are compared by their buyout price
+    let listings = vec![
+        get_listing(
+            1,
+            Some(9000),
+            "https://page.auctions.yahoo.co.jp/jp/auction/x2",
+        ),
+        get_listing(
+            1,
+            Some(7000),
+            "https://page.auctions.yahoo.co.jp/jp/auction/x3",
+        ),
+        get_listing(1, None, "https://page.auctions.yahoo.co.jp/jp/auction/x1"),
+    ];
+    let lowest_prices = ModulePool::get_lowest_prices(&item, &listings);
+    assert_eq!(lowest_prices.len(), 1);
+    assert_eq!(
+        lowest_prices[0].price,
+        Money::new(7000, SupportedCurrency::JPY)
+    );
+    assert_eq!(
+        lowest_prices[0].url,
+        "https://page.auctions.yahoo.co.jp/jp/auction/x3"
+    );
+
+    // the low bids therefore don't trigger plain below price conditions
+    let condition = Condition::new(ConditionType::BelowPrice, ItemConditions::All, 50.0, 1);
+    assert!(db.matches_condition(listings[2].price.clone(), condition.clone()));
+    for price in lowest_prices {
+        assert!(!db.matches_condition(price, condition.clone()));
+    }
+}
@@ -1,14 +1,13 @@
-use std::borrow::Borrow;
 use std::error::Error;
 
 use kuchiki::traits::TendrilSink;
 
-use crate::configuration::Configuration;
 use crate::currency::guesser::CurrencyGuesser;
-use crate::database::items::{Item, ItemConditions};
+use crate::database::items::{Grade, Item, ItemConditions};
+use crate::database::listings::Listing;
 use crate::database::prices::Price;
 use crate::modules::myfigurecollection::MyFigureCollection;
-use crate::modules::{BaseModule, Prices};
+use crate::modules::BaseModule;
 
 struct Base<'a> {
     pub(crate) inner: &'a MyFigureCollection,
@@ -20,9 +19,9 @@ impl<'a> Base<'a> {
         &self,
         item: Item,
         search_url: String,
-    ) -> Result<Vec<Price>, Box<dyn Error>> {
+    ) -> Result<Vec<Listing>, Box<dyn Error>> {
         let mut sales = vec![];
-        let used_currency = Configuration::get_used_currency();
+        let used_currency = self.inner.config.general.currency.clone();
         let mut res = self.inner.client.get(search_url.as_str()).send()?;
 
         loop {
@@ -45,27 +44,48 @@ impl<'a> Base<'a> {
                     .get("href")
                     .unwrap()
                     .to_string();
+                let seller = match element_node.select_first("a[href*='/profile/']") {
+                    Ok(seller) => seller.text_contents().trim().to_string(),
+                    Err(_) => "".to_string(),
+                };
 
                 if let Some(currency) = CurrencyGuesser::new().guess_currency(currency) {
-                    if let Ok(price_value) = CurrencyGuesser::get_currency_value(price.clone()) {
+                    if let Ok(price_value) =
+                        CurrencyGuesser::get_currency_value(price.clone(), currency.clone())
+                    {
                         let mut price = Price::new(
                             item.clone(),
                             price_value,
-                            currency.clone(),
                             sale_url,
                             MyFigureCollection::get_module_key(),
                             ItemConditions::New,
                         );
-                        price.converted_price = self.inner.conversion.convert_price_to(
-                            price.price,
-                            currency.clone(),
-                            used_currency.clone(),
-                        );
-                        price.converted_currency = used_currency.clone().to_string();
-                        price.taxes = Configuration::get_used_tax_rate(currency.clone());
-                        price.shipping = Configuration::get_shipping(currency.clone());
-
-                        sales.push(price);
+                        // listings which can't be compared to the other listings are skipped
+                        match self
+                            .inner
+                            .conversion
+                            .convert_price_to(&price.price, used_currency.clone())
+                        {
+                            Ok(converted_price) => price.set_conversion(
+                                converted_price,
+                                &self.inner.config.conversion.get_listing_costs(
+                                    &price.module,
+                                    &currency,
+                                    item.jan,
+                                ),
+                            ),
+                            Err(err) => {
+                                warn!(
+                                    "[{}] - unable to convert listing price {} (err: {:?})",
+                                    MyFigureCollection::get_module_key(),
+                                    price.price,
+                                    err
+                                );
+                                continue;
+                            }
+                        }
+
+                        sales.push(Listing::new(price, seller, "".to_string()));
                     }
                 }
             }
@@ -92,7 +112,7 @@ impl<'a> Base<'a> {
     }
 
     /// retrieve all sales
-    fn get_all_sales(&self, item: Item, figure_id: u32) -> Result<Vec<Price>, Box<dyn Error>> {
+    fn get_all_sales(&self, item: Item, figure_id: u32) -> Result<Vec<Listing>, Box<dyn Error>> {
         let search_url = format!(
             "https://myfigurecollection.net/classified.php?type=0&itemId={}",
             figure_id
@@ -102,30 +122,37 @@ impl<'a> Base<'a> {
     }
 
     /// retrieve new sales
-    fn get_new_sales(&self, item: Item, figure_id: u32) -> Result<Vec<Price>, Box<dyn Error>> {
+    fn get_new_sales(&self, item: Item, figure_id: u32) -> Result<Vec<Listing>, Box<dyn Error>> {
         let search_url = format!(
             "https://myfigurecollection.net/classified.php?type=0&itemId={}&isMIB=1",
             figure_id
         );
 
-        self.get_sales_from_url(item, search_url)
+        let mut sales = self.get_sales_from_url(item, search_url)?;
+        for sale in sales.iter_mut() {
+            sale.grade = "MIB".to_string();
+            sale.price.item_grade = Some(Grade::S);
+            sale.price.box_grade = Some(Grade::S);
+        }
+
+        Ok(sales)
     }
 
     /// retrieve used sales
     /// (MFC doesn't display mint/used difference in sales page, so we have to retrieve the differences ourselves)
-    fn get_used_sales(all_sales: &[Price], new_sales: &[Price]) -> Vec<Price> {
+    fn get_used_sales(all_sales: &[Listing], new_sales: &[Listing]) -> Vec<Listing> {
         let mut sales = vec![];
 
         'outer: for sale in all_sales {
             let mut sale = sale.clone();
             // check for identical ad IDs of new sales and continue if found
             for new_sale in new_sales {
-                if new_sale.url == sale.url {
+                if new_sale.price.url == sale.price.url {
                     continue 'outer;
                 }
             }
             // else it's used and we append it to our used sales
-            sale.condition = ItemConditions::Used;
+            sale.price.condition = ItemConditions::Used;
             sales.push(sale)
         }
 
@@ -139,44 +166,27 @@ impl BaseModule for MyFigureCollection {
         MyFigureCollection::get_module_key()
     }
 
-    /// retrieve the lowest price for new and used condition
-    fn get_lowest_prices(&self, item: &Item) -> Result<Prices, Box<dyn Error>> {
+    /// retrieve the classifieds as listings, classifieds not listed as mint in box are used
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
         let figure_id = self.get_figure_id(&item)?;
         let all_sales = Base { inner: self }.get_all_sales(item.clone(), figure_id.clone())?;
-        let new_sales = Base { inner: self }.get_new_sales(item.clone(), figure_id)?;
+        let mut new_sales = Base { inner: self }.get_new_sales(item.clone(), figure_id)?;
 
         let used_sales = Base::get_used_sales(&all_sales, &new_sales);
+        new_sales.extend(used_sales);
 
-        let mut prices = Prices {
-            new: None,
-            used: None,
-        };
-
-        for new_sale in new_sales {
-            if prices.borrow().new.is_none()
-                || new_sale.get_converted_total()
-                    < prices.borrow().new.as_ref().unwrap().get_converted_total()
-            {
-                prices.new = Some(new_sale);
-            }
-        }
-
-        for used_sale in used_sales {
-            if prices.borrow().used.is_none()
-                || used_sale.get_converted_total()
-                    < prices.borrow().used.as_ref().unwrap().get_converted_total()
-            {
-                prices.used = Some(used_sale);
-            }
-        }
-
-        Ok(prices)
+        Ok(new_sales)
     }
 }
 
 #[test]
 pub fn test_get_lowest_prices() {
+    use crate::configuration::Config;
     use crate::currency::conversion::CurrencyConversion;
+    use crate::currency::money::Money;
+    use crate::currency::SupportedCurrency;
+    use crate::http::Client;
+    use crate::modules::Module;
     use std::collections::BTreeMap;
 
     let item = &mut Item {
@@ -189,15 +199,40 @@ pub fn test_get_lowest_prices() {
         disabled: false,
     };
 
+    let mut exchange_rates = BTreeMap::new();
+    exchange_rates.insert(SupportedCurrency::EUR, 1.0);
+    exchange_rates.insert(SupportedCurrency::JPY, 120.0);
+    exchange_rates.insert(SupportedCurrency::USD, 1.1);
+
     let mfc = MyFigureCollection {
-        conversion: CurrencyConversion {
-            exchange_rates: BTreeMap::new(),
-        },
-        client: reqwest::blocking::Client::builder().build().unwrap(),
+        conversion: CurrencyConversion { exchange_rates },
+        client: Client::from_fixture("myfigurecollection/lowest_prices"),
+        config: Config::default(),
     };
 
-    assert!(mfc.get_lowest_prices(item).is_ok());
-    if let Err(err) = mfc.get_lowest_prices(item) {
-        println!("{}", err)
-    }
+    // all classifieds are listings of their seller
+    let listings = mfc.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 3);
+    assert_eq!(listings[0].seller, "figurefan");
+    assert_eq!(listings[0].grade, "MIB");
+    assert_eq!(listings[0].price.box_grade, Some(Grade::S));
+    // loose sales aren't graded
+    assert_eq!(listings[2].price.item_grade, None);
+
+    let prices = mfc.get_lowest_prices(item).unwrap();
+
+    // the lowest new price is compared by the converted price and not the original price
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(9800, SupportedCurrency::JPY));
+    assert_eq!(
+        new.converted_price,
+        Money::new(8167, SupportedCurrency::EUR)
+    );
+    assert_eq!(new.url, "https://myfigurecollection.net/classified/201001");
+
+    // sales not listed as mint in box are used sales
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(8500, SupportedCurrency::EUR));
+    assert_eq!(used.condition, ItemConditions::Used);
+    assert_eq!(used.url, "https://myfigurecollection.net/classified/201002");
 }
@@ -5,9 +5,10 @@ use kuchiki::traits::TendrilSink;
 use kuchiki::NodeRef;
 use regex::Regex;
 
+use crate::configuration::Config;
 use crate::currency::conversion::CurrencyConversion;
 use crate::database::items::Item;
-use crate::http::get_client;
+use crate::http::Client;
 use crate::modules::NotFoundError;
 
 mod base;
@@ -15,16 +16,18 @@ mod info;
 
 #[derive(Clone)]
 pub(crate) struct MyFigureCollection {
-    client: reqwest::blocking::Client,
+    client: Client,
     conversion: CurrencyConversion,
+    config: Config,
 }
 
 impl MyFigureCollection {
     /// create new instance of MFC
-    pub fn new(conversion: CurrencyConversion) -> Result<Self, Box<dyn Error>> {
+    pub fn new(conversion: CurrencyConversion, config: &Config) -> Result<Self, Box<dyn Error>> {
         Ok(MyFigureCollection {
-            client: get_client()?,
+            client: Client::new(&config.connection)?,
             conversion,
+            config: config.clone(),
         })
     }
 
@@ -90,8 +93,8 @@ impl MyFigureCollection {
             .get(MyFigureCollection::get_figure_search_url(&item).as_str())
             .send()?;
 
-        // move response URL from Url -> &str -> String since retrieving the text will move the value
-        let res_url = res.url().as_str().to_string();
+        // copy the response URL since retrieving the text will move the value
+        let res_url = res.url().to_string();
 
         if !figure_id_regex.is_match(res_url.as_str()) {
             let doc = kuchiki::parse_html().one(res.text()?.as_str());
@@ -136,7 +139,8 @@ pub fn test_get_figure_id() {
         conversion: CurrencyConversion {
             exchange_rates: BTreeMap::new(),
         },
-        client: reqwest::blocking::Client::builder().build().unwrap(),
+        client: Client::from_fixture("myfigurecollection/figure_id"),
+        config: Config::default(),
     };
 
     assert!(mfc.get_figure_id(item).is_ok());
@@ -165,13 +169,19 @@ pub fn test_get_figure_details() {
         conversion: CurrencyConversion {
             exchange_rates: BTreeMap::new(),
         },
-        client: reqwest::blocking::Client::builder().build().unwrap(),
+        client: Client::from_fixture("myfigurecollection/figure_details"),
+        config: Config::default(),
     };
 
     assert!(mfc.update_figure_details(item).is_ok());
-
-    println!("JAN: {:?}", item.jan);
-    println!("description: {:?}", item.description);
-    println!("english term: {:?}", item.term_en);
-    println!("japanese term: {:?}", item.term_jp);
+    assert_eq!(
+        item.description,
+        "Original Character - Sophia F. Shirring - B-style - 1/4 - Bunny Ver."
+    );
+    assert_eq!(
+        item.image,
+        "https://static.myfigurecollection.net/pics/figure/large/740256.jpg"
+    );
+    assert_eq!(item.term_en, "Sophia F. Shirring FREEing 1/4");
+    assert_eq!(item.term_jp, "ソフィア・F・シャーリング フリーイング 1/4");
 }
@@ -1,13 +1,40 @@
 use std::error::Error;
 
+use chrono::NaiveDate;
 use kuchiki::traits::TendrilSink;
+use regex::Regex;
 
 use crate::currency::guesser::CurrencyGuesser;
 use crate::currency::SupportedCurrency;
 use crate::database::items::{Item, ItemConditions};
-use crate::database::prices::Price;
+use crate::database::listings::Listing;
+use crate::database::prices::{Availability, Price};
 use crate::modules::rakuten::Rakuten;
-use crate::modules::{BaseModule, Prices};
+use crate::modules::{get_grades_from_title, BaseModule};
+
+impl Rakuten {
+    /// pre-orders (予約) often contain the planned release month in the title, f.e. "2024年5月発売予定",
+    /// items which have to be ordered by the shop first are marked with お取り寄せ
+    fn get_availability_from_title(title: &str) -> (Availability, Option<NaiveDate>) {
+        if title.contains("予約") {
+            let release_month = Regex::new(r"(?P<year>\d{4})年(?P<month>\d{1,2})月")
+                .unwrap()
+                .captures(title)
+                .and_then(|captures| {
+                    NaiveDate::from_ymd_opt(
+                        captures["year"].parse().ok()?,
+                        captures["month"].parse().ok()?,
+                        1,
+                    )
+                });
+            (Availability::PreOrder, release_month)
+        } else if title.contains("お取り寄せ") {
+            (Availability::Backorder, None)
+        } else {
+            (Availability::InStock, None)
+        }
+    }
+}
 
 impl BaseModule for Rakuten {
     /// retrieve the module key
@@ -15,14 +42,9 @@ impl BaseModule for Rakuten {
         Rakuten::get_module_key()
     }
 
-    /// retrieve the lowest price for new and used condition
-    fn get_lowest_prices(&self, item: &Item) -> Result<Prices, Box<dyn Error>> {
-        let mut prices = Prices {
-            used: None,
-            new: None,
-        };
-
-        let mut collected_prices = vec![];
+    /// retrieve all listings of the search results
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
 
         let search_url = format!("https://search.rakuten.co.jp/search/mall/{}/", item.jan);
         let res = self.client.get(search_url.as_str()).send()?;
@@ -33,14 +55,15 @@ impl BaseModule for Rakuten {
             .unwrap()
         {
             let sale = css_match.as_node();
-            let cond = if let Ok(cond) = sale.select_first("span.dui-tag.-type") {
-                match cond.text_contents().as_str() {
-                    "中古" => ItemConditions::Used,
-                    "新品" => ItemConditions::New,
-                    _ => ItemConditions::New,
-                }
+            let grade = if let Ok(cond) = sale.select_first("span.dui-tag.-type") {
+                cond.text_contents()
             } else {
-                ItemConditions::New
+                "".to_string()
+            };
+            let cond = match grade.as_str() {
+                "中古" => ItemConditions::Used,
+                "新品" => ItemConditions::New,
+                _ => ItemConditions::New,
             };
 
             let price_text = sale
@@ -48,51 +71,51 @@ impl BaseModule for Rakuten {
                 .unwrap()
                 .text_contents();
 
-            let sale_url = sale
-                .select_first("h2 > a[title]")
-                .unwrap()
+            let sale_link = sale.select_first("h2 > a[title]").unwrap();
+            let sale_url = sale_link
                 .attributes
                 .borrow()
                 .get("href")
                 .unwrap()
                 .to_string();
+            let title = sale_link
+                .attributes
+                .borrow()
+                .get("title")
+                .unwrap_or_default()
+                .to_string();
+            let (item_grade, box_grade) = get_grades_from_title(&title);
+            let (availability, release_month) = Rakuten::get_availability_from_title(&title);
 
-            collected_prices.push(Price::new(
-                item.clone(),
-                CurrencyGuesser::get_currency_value(price_text)?,
-                SupportedCurrency::JPY,
-                sale_url,
-                Rakuten::get_module_key(),
-                cond,
-            ));
-        }
+            // the shop code is the first path segment of the item URL
+            let seller = sale_url.split('/').nth(3).unwrap_or_default().to_string();
 
-        for price in collected_prices {
-            match price.condition {
-                ItemConditions::New => {
-                    if prices.new.clone().is_none()
-                        || price.price < prices.new.clone().unwrap().price
-                    {
-                        prices.new = Some(price)
-                    }
-                }
-                ItemConditions::Used => {
-                    if prices.used.clone().is_none()
-                        || price.price < prices.used.clone().unwrap().price
-                    {
-                        prices.used = Some(price)
-                    }
-                }
-                _ => {}
-            }
+            listings.push(Listing::new(
+                Price::new(
+                    item.clone(),
+                    CurrencyGuesser::get_currency_value(price_text, SupportedCurrency::JPY)?,
+                    sale_url,
+                    Rakuten::get_module_key(),
+                    cond,
+                )
+                .with_grades(item_grade, box_grade)
+                .with_availability(availability, release_month),
+                seller,
+                grade,
+            ));
         }
 
-        Ok(prices)
+        Ok(listings)
     }
 }
 
 #[test]
 pub fn test_get_lowest_prices() {
+    use crate::currency::money::Money;
+    use crate::database::items::Grade;
+    use crate::http::Client;
+    use crate::modules::Module;
+
     let item = &mut Item {
         id: 0,
         jan: 4_545_784_042_649,
@@ -104,14 +127,36 @@ pub fn test_get_lowest_prices() {
     };
 
     let rakuten = Rakuten {
-        client: reqwest::blocking::Client::builder().build().unwrap(),
+        client: Client::from_fixture("rakuten/lowest_prices"),
     };
 
-    match rakuten.get_lowest_prices(item) {
-        Ok(prices) => {
-            println!("lowest price new: {}", prices.new.unwrap().price);
-            println!("lowest price used: {}", prices.used.unwrap().price);
-        }
-        Err(err) => println!("{}", err),
-    }
+    // all search results are listings, the shop is the seller of the listing
+    let listings = rakuten.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 4);
+    assert_eq!(listings[3].seller, "mandarake");
+    assert_eq!(listings[3].grade, "中古");
+    assert_eq!(listings[3].price.item_grade, None);
+    assert_eq!(listings[3].price.box_grade, Some(Grade::C));
+    assert_eq!(listings[2].price.availability, Availability::PreOrder);
+    assert_eq!(
+        listings[2].price.release_month,
+        NaiveDate::from_ymd_opt(2024, 5, 1)
+    );
+
+    let prices = rakuten.get_lowest_prices(item).unwrap();
+
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(11980, SupportedCurrency::JPY));
+    assert_eq!(
+        new.url,
+        "https://item.rakuten.co.jp/hobbystock/hs-4545784042649/"
+    );
+
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(9480, SupportedCurrency::JPY));
+    assert_eq!(used.condition, ItemConditions::Used);
+    assert_eq!(
+        used.url,
+        "https://item.rakuten.co.jp/surugaya-a-too/602099358/"
+    );
 }
@@ -1,19 +1,20 @@
 use std::error::Error;
 
-use crate::http::get_client;
+use crate::configuration::Config;
+use crate::http::Client;
 
 mod base;
 
 #[derive(Clone)]
 pub(crate) struct Rakuten {
-    client: reqwest::blocking::Client,
+    client: Client,
 }
 
 impl Rakuten {
     /// create new instance of Rakuten
-    pub fn new() -> Result<Self, Box<dyn Error>> {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
         Ok(Rakuten {
-            client: get_client()?,
+            client: Client::new(&config.connection)?,
         })
     }
 
@@ -5,9 +5,11 @@ use serde::Deserialize;
 use crate::currency::guesser::CurrencyGuesser;
 use crate::currency::SupportedCurrency;
 use crate::database::items::{Item, ItemConditions};
-use crate::database::prices::Price;
+use crate::database::listings::Listing;
+use crate::database::prices::{Availability, Price};
+use crate::http::Client;
 use crate::modules::solarisjapan::SolarisJapan;
-use crate::modules::{BaseModule, Prices};
+use crate::modules::BaseModule;
 
 /// the search response returns from the API of SolarisJapan
 #[derive(Deserialize)]
@@ -45,7 +47,7 @@ impl ApiSearchItem {
         format!("https://solarisjapan.com/products/{}", self.handle)
     }
 
-    pub fn get_info(&self, client: reqwest::blocking::Client) -> Result<ApiInfo, Box<dyn Error>> {
+    pub fn get_info(&self, client: &Client) -> Result<ApiInfo, Box<dyn Error>> {
         let info_url = format!("{}.json", self.get_url());
 
         let res = client.get(info_url.as_str()).send()?;
@@ -62,9 +64,27 @@ struct ApiInfo {
 
 #[derive(Deserialize)]
 struct Product {
+    /// comma separated tags of the product
+    #[serde(default)]
+    tags: String,
     variants: Vec<ProductVariant>,
 }
 
+impl Product {
+    /// not yet released products are tagged as pre-order, the release month isn't published
+    fn get_availability(&self) -> Availability {
+        if self
+            .tags
+            .split(',')
+            .any(|tag| tag.trim().eq_ignore_ascii_case("pre-order"))
+        {
+            Availability::PreOrder
+        } else {
+            Availability::InStock
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct ProductVariant {
     title: String,
@@ -104,8 +124,8 @@ impl BaseModule for SolarisJapan {
         SolarisJapan::get_module_key()
     }
 
-    /// retrieve the lowest price for new and used condition
-    fn get_lowest_prices(&self, item: &Item) -> Result<Prices, Box<dyn Error>> {
+    /// retrieve the listings of the product variants
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
         let api_url = "https://zzb7273v5r-dsn.algolia.net/1/indexes/*/queries\
             ?x-algolia-api-key=159b58d793c7a4ebd5928c6b8c100941\
             &x-algolia-application-id=ZZB7273V5R\
@@ -120,51 +140,57 @@ impl BaseModule for SolarisJapan {
             ]
         }}", item.jan, item.jan);
 
-        let mut prices = Prices {
-            used: None,
-            new: None,
-        };
+        let mut listings = vec![];
 
         let res = self.client.post(api_url).body(data).send()?;
         let deserialized_data: ApiSearchResponse = serde_json::from_str(&res.text()?)?;
 
         if let Some(search_result) = Base::get_closest_search_result(deserialized_data) {
-            if let Ok(info) = search_result.get_info(self.clone().client) {
+            if let Ok(info) = search_result.get_info(&self.client) {
+                let availability = info.product.get_availability();
                 for variant in info.product.variants {
                     // not available, skip here
                     if variant.price.as_str() == "0" {
                         continue;
                     }
 
-                    let mut price = Price::new(
-                        item.clone(),
-                        CurrencyGuesser::get_currency_value(variant.price)?,
-                        SupportedCurrency::JPY,
-                        search_result.get_url(),
-                        SolarisJapan::get_module_key(),
-                        ItemConditions::New,
-                    );
-
-                    match variant.title.as_str() {
-                        "Brand New" => {
-                            prices.new = Some(price);
-                        }
-                        "Pre Owned" => {
-                            price.condition = ItemConditions::Used;
-                            prices.used = Some(price);
+                    let cond = match variant.title.as_str() {
+                        "Brand New" => ItemConditions::New,
+                        "Pre Owned" => ItemConditions::Used,
+                        _ => {
+                            warn!("unknown condition: {}", variant.title);
+                            continue;
                         }
-                        _ => warn!("unknown condition: {}", variant.title),
-                    }
+                    };
+
+                    listings.push(Listing::new(
+                        Price::new(
+                            item.clone(),
+                            CurrencyGuesser::get_currency_value(
+                                variant.price,
+                                SupportedCurrency::JPY,
+                            )?,
+                            search_result.get_url(),
+                            SolarisJapan::get_module_key(),
+                            cond,
+                        )
+                        .with_availability(availability, None),
+                        "".to_string(),
+                        variant.title,
+                    ));
                 }
             }
         }
 
-        Ok(prices)
+        Ok(listings)
     }
 }
 
 #[test]
 pub fn test_get_lowest_prices() {
+    use crate::currency::money::Money;
+    use crate::modules::Module;
+
     let item = &mut Item {
         id: 0,
         jan: 4_934_054_783_441,
@@ -176,10 +202,24 @@ pub fn test_get_lowest_prices() {
     };
 
     let solaris = SolarisJapan {
-        client: reqwest::blocking::Client::builder().build().unwrap(),
+        client: Client::from_fixture("solarisjapan/lowest_prices"),
     };
 
-    if let Err(err) = solaris.get_lowest_prices(item) {
-        println!("{}", err)
-    }
+    let listings = solaris.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 2);
+    assert_eq!(listings[0].price.availability, Availability::PreOrder);
+
+    let prices = solaris.get_lowest_prices(item).unwrap();
+
+    // the exact EAN match is used instead of the first search result
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(15800, SupportedCurrency::JPY));
+    assert_eq!(
+        new.url,
+        "https://solarisjapan.com/products/fate-grand-order-saber-lily-1-7-complete-figure"
+    );
+
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(11200, SupportedCurrency::JPY));
+    assert_eq!(used.condition, ItemConditions::Used);
 }
@@ -0,0 +1,252 @@
+use std::error::Error;
+
+use kuchiki::traits::TendrilSink;
+use kuchiki::NodeRef;
+
+use crate::currency::guesser::CurrencyGuesser;
+use crate::currency::SupportedCurrency;
+use crate::database::items::{Item, ItemConditions};
+use crate::database::listings::Listing;
+use crate::database::prices::Price;
+use crate::modules::surugaya::SurugaYa;
+use crate::modules::{get_grades_from_title, BaseModule};
+
+impl SurugaYa {
+    /// retrieve the condition from the condition label of the shop
+    fn get_condition(label: &str) -> Option<ItemConditions> {
+        match label.trim() {
+            "新品" => Some(ItemConditions::New),
+            "中古" => Some(ItemConditions::Used),
+            _ => None,
+        }
+    }
+
+    /// retrieve the URLs of the product pages from the search results
+    fn search(&self, item: &Item) -> Result<Vec<String>, Box<dyn Error>> {
+        let search_url = format!(
+            "https://www.suruga-ya.jp/search?category=&search_word={}",
+            item.jan
+        );
+        let res = self.client.get(search_url.as_str()).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        let product_urls = doc
+            .select("div.item p.title a[href*='/product/detail/']")
+            .unwrap()
+            .filter_map(|link| {
+                link.attributes
+                    .borrow()
+                    .get("href")
+                    .map(|href| format!("https://www.suruga-ya.jp{}", href))
+            })
+            .collect();
+
+        Ok(product_urls)
+    }
+
+    /// retrieve the new and used prices of the shop and the marketplace offers from the product page,
+    /// products of other JAN numbers from the search results are skipped
+    fn get_product_listings(
+        &self,
+        item: &Item,
+        product_url: &str,
+    ) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+
+        let res = self.client.get(product_url).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        let jan = doc
+            .select_first("table.tbl_product_info td.jan")
+            .map(|jan| jan.text_contents().trim().to_string())
+            .unwrap_or_default();
+        if jan != item.jan.to_string() {
+            debug!(
+                "[{}] - skipping product {} with JAN {:?}",
+                SurugaYa::get_module_key(),
+                product_url,
+                jan
+            );
+            return Ok(listings);
+        }
+
+        for price_group in doc.select("div.item_price div.price_group").unwrap() {
+            let price_group = price_group.as_node();
+            let cond = match price_group.select_first("label") {
+                Ok(label) => match SurugaYa::get_condition(&label.text_contents()) {
+                    Some(cond) => cond,
+                    None => continue,
+                },
+                Err(_) => continue,
+            };
+            let price_text = match price_group.select_first("span.text-price-detail") {
+                Ok(price) => price.text_contents(),
+                Err(_) => continue,
+            };
+            let price =
+                match CurrencyGuesser::get_currency_value(price_text, SupportedCurrency::JPY) {
+                    Ok(price) => price,
+                    Err(err) => {
+                        warn!(
+                            "[{}] - unable to parse the price of {} (err: {:?})",
+                            SurugaYa::get_module_key(),
+                            product_url,
+                            err
+                        );
+                        continue;
+                    }
+                };
+
+            let mut listing = Listing::new(
+                Price::new(
+                    item.clone(),
+                    price,
+                    product_url.to_string(),
+                    SurugaYa::get_module_key(),
+                    cond,
+                ),
+                "".to_string(),
+                "".to_string(),
+            );
+            listing.in_stock = price_group.select_first("span.soldout").is_err();
+            listings.push(listing);
+        }
+
+        // the marketplace (マケプレ) offers of other shops are listed on a separate page
+        let marketplace_url = doc
+            .select_first("a[href*='/product/other/']")
+            .ok()
+            .and_then(|link| {
+                link.attributes
+                    .borrow()
+                    .get("href")
+                    .map(|href| format!("https://www.suruga-ya.jp{}", href))
+            });
+        if let Some(marketplace_url) = marketplace_url {
+            listings.extend(self.get_marketplace_listings(item, &marketplace_url)?);
+        }
+
+        Ok(listings)
+    }
+
+    /// retrieve the marketplace offers of other shops, the grades are taken from the notes
+    fn get_marketplace_listings(
+        &self,
+        item: &Item,
+        marketplace_url: &str,
+    ) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+
+        let res = self.client.get(marketplace_url).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        for offer in doc.select("table.tbl_all_item tr.item").unwrap() {
+            let offer = offer.as_node();
+            let cond = match SurugaYa::get_condition(&SurugaYa::get_text(offer, "td.condition")) {
+                Some(cond) => cond,
+                None => continue,
+            };
+            // the offers are free-form rows of other shops, offers without a valid price are skipped
+            let price = match CurrencyGuesser::get_currency_value(
+                SurugaYa::get_text(offer, "td.price strong"),
+                SupportedCurrency::JPY,
+            ) {
+                Ok(price) => price,
+                Err(err) => {
+                    warn!(
+                        "[{}] - unable to parse the price of an offer on {} (err: {:?})",
+                        SurugaYa::get_module_key(),
+                        marketplace_url,
+                        err
+                    );
+                    continue;
+                }
+            };
+            let note = SurugaYa::get_text(offer, "td.note");
+            let (item_grade, box_grade) = get_grades_from_title(&note);
+
+            listings.push(Listing::new(
+                Price::new(
+                    item.clone(),
+                    price,
+                    marketplace_url.to_string(),
+                    SurugaYa::get_module_key(),
+                    cond,
+                )
+                .with_grades(item_grade, box_grade),
+                SurugaYa::get_text(offer, "td.shop"),
+                note,
+            ));
+        }
+
+        Ok(listings)
+    }
+
+    /// retrieve the trimmed text of the first element matching the selector
+    fn get_text(node: &NodeRef, selector: &str) -> String {
+        match node.select_first(selector) {
+            Ok(element) => element.text_contents().trim().to_string(),
+            Err(_) => "".to_string(),
+        }
+    }
+}
+
+impl BaseModule for SurugaYa {
+    /// retrieve the module key
+    fn get_module_key(&self) -> String {
+        SurugaYa::get_module_key()
+    }
+
+    /// retrieve the shop and marketplace listings of all products matching the JAN
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+        for product_url in self.search(item)? {
+            listings.extend(self.get_product_listings(item, &product_url)?);
+        }
+
+        Ok(listings)
+    }
+}
+
+#[test]
+pub fn test_get_lowest_prices() {
+    use crate::currency::money::Money;
+    use crate::database::items::Grade;
+    use crate::http::Client;
+    use crate::modules::Module;
+
+    let item = &mut Item {
+        id: 0,
+        jan: 4_545_784_042_649,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "".to_string(),
+        disabled: false,
+    };
+
+    let surugaya = SurugaYa {
+        client: Client::from_fixture("surugaya/lowest_prices"),
+    };
+
+    // products of other JAN numbers and offers without a valid price are skipped,
+    // the shop itself has no seller name
+    let listings = surugaya.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 4);
+    assert_eq!(listings[0].seller, "");
+    assert!(!listings[0].in_stock);
+    assert_eq!(listings[2].seller, "ホビーショップ星");
+    assert_eq!(listings[2].price.box_grade, Some(Grade::C));
+
+    let prices = surugaya.get_lowest_prices(item).unwrap();
+
+    // sold out prices of the shop are ignored
+    let new = prices.new.unwrap();
+    assert_eq!(new.price, Money::new(12300, SupportedCurrency::JPY));
+    assert_eq!(new.url, "https://www.suruga-ya.jp/product/other/602099358");
+
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(8800, SupportedCurrency::JPY));
+    assert_eq!(used.condition, ItemConditions::Used);
+    assert_eq!(listings[2].price.price, used.price);
+}
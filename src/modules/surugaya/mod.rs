@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use crate::configuration::Config;
+use crate::http::Client;
+
+mod base;
+
+#[derive(Clone)]
+pub(crate) struct SurugaYa {
+    client: Client,
+}
+
+impl SurugaYa {
+    /// create new instance of SurugaYa
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        Ok(SurugaYa {
+            client: Client::new(&config.connection)?,
+        })
+    }
+
+    pub fn get_module_key() -> String {
+        "suruga-ya.jp".to_string()
+    }
+}
@@ -0,0 +1,172 @@
+use std::error::Error;
+
+use chrono::{TimeZone, Utc};
+use kuchiki::traits::TendrilSink;
+use reqwest::Url;
+
+use crate::currency::money::Money;
+use crate::currency::SupportedCurrency;
+use crate::database::items::{Item, ItemConditions};
+use crate::database::listings::Listing;
+use crate::database::prices::Price;
+use crate::modules::yahoo_auctions::YahooAuctions;
+use crate::modules::{get_grades_from_title, BaseModule};
+
+impl YahooAuctions {
+    /// retrieve the auctions of the search results for the passed keyword
+    fn search(&self, item: &Item, keyword: &str) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = vec![];
+
+        let search_url = Url::parse_with_params(
+            "https://auctions.yahoo.co.jp/search/search",
+            &[("p", keyword)],
+        )?;
+        let res = self.client.get(search_url.as_str()).send()?;
+        let doc = kuchiki::parse_html().one(res.text()?.as_str());
+
+        for css_match in doc.select("ul.Products__items li.Product").unwrap() {
+            let product = css_match.as_node();
+            // the auction details are attached to the title link as data attributes,
+            // products which can't be parsed are skipped instead of failing the whole check
+            let title_link = match product.select_first("a.Product__titleLink") {
+                Ok(title_link) => title_link,
+                Err(_) => continue,
+            };
+            let attributes = title_link.attributes.borrow();
+            let get_amount = |name: &str| -> Option<i64> {
+                attributes
+                    .get(name)
+                    .and_then(|value| value.trim().parse::<i64>().ok())
+            };
+
+            let title = attributes
+                .get("data-auction-title")
+                .unwrap_or_default()
+                .to_string();
+            let sale_url = match attributes.get("href") {
+                Some(href) => href.to_string(),
+                None => continue,
+            };
+            let bid = match get_amount("data-auction-price") {
+                Some(bid) => bid,
+                None => continue,
+            };
+            // auctions without buy-it-now option have a buyout price of 0
+            let buyout_price = get_amount("data-auction-buynowprice")
+                .filter(|buyout_price| *buyout_price > 0)
+                .map(|buyout_price| Money::new(buyout_price, SupportedCurrency::JPY));
+            let auction_end = match get_amount("data-auction-endtime")
+                .and_then(|end_time| Utc.timestamp_opt(end_time, 0).single())
+            {
+                Some(auction_end) => auction_end,
+                None => continue,
+            };
+            let seller = match product.select_first("a.Product__seller") {
+                Ok(seller) => seller.text_contents().trim().to_string(),
+                Err(_) => "".to_string(),
+            };
+
+            let cond = if title.contains("新品") || title.contains("未開封") {
+                ItemConditions::New
+            } else {
+                ItemConditions::Used
+            };
+            let (item_grade, box_grade) = get_grades_from_title(&title);
+
+            listings.push(Listing::new(
+                Price::new(
+                    item.clone(),
+                    Money::new(bid, SupportedCurrency::JPY),
+                    sale_url,
+                    YahooAuctions::get_module_key(),
+                    cond,
+                )
+                .with_grades(item_grade, box_grade)
+                .with_auction(buyout_price, auction_end),
+                seller,
+                "".to_string(),
+            ));
+        }
+
+        Ok(listings)
+    }
+}
+
+impl BaseModule for YahooAuctions {
+    /// retrieve the module key
+    fn get_module_key(&self) -> String {
+        YahooAuctions::get_module_key()
+    }
+
+    /// retrieve the auctions of the search by the JAN and by the japanese term of the item,
+    /// most auctions of private sellers don't include the JAN
+    fn get_listings(&self, item: &Item) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let mut listings = self.search(item, &item.jan.to_string())?;
+
+        if !item.term_jp.is_empty() {
+            for listing in self.search(item, &item.term_jp)? {
+                if !listings
+                    .iter()
+                    .any(|known_listing| known_listing.price.url == listing.price.url)
+                {
+                    listings.push(listing);
+                }
+            }
+        }
+
+        Ok(listings)
+    }
+}
+
+#[test]
+pub fn test_get_lowest_prices() {
+    use crate::database::items::Grade;
+    use crate::http::Client;
+    use crate::modules::Module;
+
+    let item = &mut Item {
+        id: 0,
+        jan: 4_545_784_042_649,
+        description: "".to_string(),
+        image: "".to_string(),
+        term_en: "".to_string(),
+        term_jp: "ねんどろいど セイバー".to_string(),
+        disabled: false,
+    };
+
+    let yahoo = YahooAuctions {
+        client: Client::from_fixture("yahoo_auctions/lowest_prices"),
+    };
+
+    // auctions found by the JAN and the japanese term are only added once
+    let listings = yahoo.get_listings(item).unwrap();
+    assert_eq!(listings.len(), 3);
+    assert_eq!(listings[0].seller, "figure_store_tokyo");
+    assert_eq!(listings[0].price.condition, ItemConditions::New);
+    assert_eq!(
+        listings[0].price.buyout_price,
+        Some(Money::new(12000, SupportedCurrency::JPY))
+    );
+    assert_eq!(
+        listings[0].price.auction_end,
+        Some(Utc.timestamp_opt(1_714_564_800, 0).unwrap())
+    );
+    assert_eq!(listings[1].price.buyout_price, None);
+    assert_eq!(listings[1].price.box_grade, Some(Grade::C));
+    assert_eq!(listings[2].seller, "collector_osaka");
+
+    // auctions are compared by their buyout price, auctions without buyout price are ignored
+    let prices = yahoo.get_lowest_prices(item).unwrap();
+    assert_eq!(
+        prices.new.unwrap().price,
+        Money::new(12000, SupportedCurrency::JPY)
+    );
+
+    let used = prices.used.unwrap();
+    assert_eq!(used.price, Money::new(6000, SupportedCurrency::JPY));
+    assert!(used.is_auction());
+    assert_eq!(
+        used.url,
+        "https://page.auctions.yahoo.co.jp/jp/auction/x100000003"
+    );
+}
@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use crate::configuration::Config;
+use crate::http::Client;
+
+mod base;
+
+#[derive(Clone)]
+pub(crate) struct YahooAuctions {
+    client: Client,
+}
+
+impl YahooAuctions {
+    /// create new instance of YahooAuctions
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        Ok(YahooAuctions {
+            client: Client::new(&config.connection)?,
+        })
+    }
+
+    pub fn get_module_key() -> String {
+        "auctions.yahoo.co.jp".to_string()
+    }
+}
@@ -10,7 +10,6 @@ use serenity::Client;
 use crate::database::conditions::Condition;
 use crate::database::items::Item;
 use crate::database::prices::Price;
-use crate::http::get_client;
 use crate::notifications::Notification;
 
 #[derive(Clone)]
@@ -34,12 +33,13 @@ impl EventHandler for Handler {}
 
 pub(crate) struct Discord {
     client: Client,
+    http_client: reqwest::blocking::Client,
     user: User,
 }
 
 impl Discord {
     /// return instance of the Discord notifications which acts as a wrapper for the serenity library
-    pub fn new(data: DiscordBotData) -> Self {
+    pub fn new(data: DiscordBotData, http_client: reqwest::blocking::Client) -> Self {
         let mut client =
             Client::new(data.client_token, Handler).expect("error creating discord client");
         client.with_framework(StandardFramework::new().configure(|c| c.prefix("!")));
@@ -59,7 +59,11 @@ impl Discord {
         let user = serde_json::from_str(user_json.as_str())
             .expect("couldn't deserialize the discord user");
 
-        Discord { client, user }
+        Discord {
+            client,
+            http_client,
+            user,
+        }
     }
 
     /// send the passed message to the configured user ID
@@ -80,7 +84,7 @@ impl Discord {
 
         // download the image to a local temporary file
         let mut tmp = tempfile::NamedTempFile::new()?;
-        get_client()?
+        self.http_client
             .get(image_url.as_str())
             .send()?
             .copy_to(&mut tmp)?;
@@ -109,12 +113,21 @@ impl Notification for Discord {
              **{}**\n\
              **{}**\n\
              \n\
-             price: **{:.2} {}**\n\
-             price with taxes: **{:.2} {}**\n\
-             price with shipping and taxes: **{:.2} {}**\n\
-             raw price: **{:.2} {}**\n\
+             price: **{}**\n\
+             price with taxes: **{}**\n\
+             \n\
+             fees: **{}**\n\
+             shipping: **{}**\n\
+             customs duty: **{}**\n\
+             taxes ({:.1}%): **{}**\n\
+             customs clearance fee: **{}**\n\
+             total: **{}**\n\
+             \n\
+             raw price: **{}**\n\
              \n\
              item condition: **{:?}**\n\
+             availability: **{}**\n\
+             auction: **{}**\n\
              \n\
              notification type: **{:?}**\n\
              requested item condition: **{:?}**\n\
@@ -124,14 +137,18 @@ impl Notification for Discord {
             item.description,
             item.jan,
             price.converted_price,
-            price.converted_currency,
             price.get_converted_taxed(),
-            price.converted_currency,
+            price.fees,
+            price.shipping,
+            price.duty,
+            price.taxes * 100.0,
+            price.get_converted_taxes(),
+            price.clearance_fee,
             price.get_converted_total(),
-            price.converted_currency,
             price.price,
-            price.currency,
             price.condition,
+            price.get_availability_text(),
+            price.get_auction_text(),
             cond.condition_type,
             cond.item_condition,
             cond.value,
@@ -1,9 +1,10 @@
 use std::error::Error;
 
-use crate::configuration::Configuration;
+use crate::configuration::Config;
 use crate::database::conditions::Condition;
 use crate::database::items::Item;
 use crate::database::prices::Price;
+use crate::http::get_client;
 use crate::notifications::discord::{Discord, DiscordBotData};
 use crate::notifications::telegram::{Telegram, TelegramApiData};
 
@@ -12,6 +13,7 @@ pub(crate) mod telegram;
 
 #[derive(Clone)]
 pub(crate) struct NotificationManager {
+    client: reqwest::blocking::Client,
     telegram_data: Option<TelegramApiData>,
     discord_data: Option<DiscordBotData>,
 }
@@ -20,37 +22,97 @@ pub(crate) trait Notification {
     fn notify(&self, item: Item, price: Price, cond: Condition) -> Result<(), Box<dyn Error>>;
 }
 
+/// NotificationResult is the result of a notification sent over a single channel
+/// the error is stored as string to be able to share the result between threads
+pub(crate) struct NotificationResult {
+    pub(crate) channel: String,
+    pub(crate) error: Option<String>,
+}
+
+impl NotificationResult {
+    fn new(channel: &str, res: Result<(), Box<dyn Error>>) -> Self {
+        NotificationResult {
+            channel: channel.to_string(),
+            error: res.err().map(|err| err.to_string()),
+        }
+    }
+}
+
 impl NotificationManager {
-    pub fn new() -> Self {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
         let mut manager = NotificationManager {
+            client: get_client(&config.connection)?,
             telegram_data: None,
             discord_data: None,
         };
 
-        if let Some(api_key) = Configuration::get_telegram_api_key() {
-            if let Some(user_id) = Configuration::get_telegram_user_id() {
-                manager.telegram_data = Some(TelegramApiData::new(api_key, user_id));
+        let telegram = &config.notifications.telegram;
+        if telegram.active {
+            if let (Some(api_key), Some(user_id)) = (&telegram.api_key, telegram.user_id) {
+                manager.telegram_data = Some(TelegramApiData::new(api_key.clone(), user_id));
             }
         }
 
-        if let Some(client_token) = Configuration::get_discord_client_token() {
-            if let Some(user_id) = Configuration::get_discord_user_id() {
-                manager.discord_data = Some(DiscordBotData::new(client_token, user_id));
+        let discord = &config.notifications.discord;
+        if discord.active {
+            if let (Some(client_token), Some(user_id)) = (&discord.client_token, discord.user_id) {
+                manager.discord_data = Some(DiscordBotData::new(client_token.clone(), user_id));
             }
         }
 
-        manager
+        Ok(manager)
     }
 
-    pub fn notify(&self, item: Item, price: Price, cond: Condition) -> Result<(), Box<dyn Error>> {
-        if let Some(data) = self.telegram_data.as_ref() {
-            Telegram::new(data.clone()).notify(item.clone(), price.clone(), cond.clone())?;
+    /// retrieve the names of all configured channels
+    pub fn get_channels(&self) -> Vec<&'static str> {
+        let mut channels = vec![];
+        if self.telegram_data.is_some() {
+            channels.push("telegram");
+        }
+        if self.discord_data.is_some() {
+            channels.push("discord");
+        }
+
+        channels
+    }
+
+    /// notify the user over the passed channels if they are configured,
+    /// a failing channel doesn't prevent the notification over the other channels
+    pub fn notify(
+        &self,
+        item: Item,
+        price: Price,
+        cond: Condition,
+        channels: &[&str],
+    ) -> Vec<NotificationResult> {
+        let mut results = vec![];
+
+        if let Some(data) = self
+            .telegram_data
+            .as_ref()
+            .filter(|_| channels.contains(&"telegram"))
+        {
+            results.push(NotificationResult::new(
+                "telegram",
+                Telegram::new(data.clone(), self.client.clone()).notify(
+                    item.clone(),
+                    price.clone(),
+                    cond.clone(),
+                ),
+            ));
         }
 
-        if let Some(data) = self.discord_data.as_ref() {
-            Discord::new(data.clone()).notify(item, price, cond)?;
+        if let Some(data) = self
+            .discord_data
+            .as_ref()
+            .filter(|_| channels.contains(&"discord"))
+        {
+            results.push(NotificationResult::new(
+                "discord",
+                Discord::new(data.clone(), self.client.clone()).notify(item, price, cond),
+            ));
         }
 
-        Ok(())
+        results
     }
 }
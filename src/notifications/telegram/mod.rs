@@ -9,7 +9,6 @@ use tempfile::tempdir;
 use crate::database::conditions::Condition;
 use crate::database::items::Item;
 use crate::database::prices::Price;
-use crate::http::get_client;
 use crate::notifications::Notification;
 
 #[derive(Clone)]
@@ -27,15 +26,17 @@ impl TelegramApiData {
 pub(crate) struct Telegram {
     api: Api,
     user_id: i64,
+    client: reqwest::blocking::Client,
 }
 
 impl Telegram {
     /// return instance of the Telegram Notifications which acts as a wrapper for the telegram-bot library
     /// it also handles the future promises and you can use it in a blocking context
-    pub fn new(data: TelegramApiData) -> Telegram {
+    pub fn new(data: TelegramApiData, client: reqwest::blocking::Client) -> Telegram {
         Telegram {
             api: Api::new(&*data.api_key),
             user_id: data.user_id,
+            client,
         }
     }
 
@@ -54,27 +55,22 @@ impl Telegram {
 
     /// send the passed image with the optional passed caption to the passed user ID
     /// the image path must be available online though and can't be an local file
-    fn send_image_message(
-        &self,
-        image_url: String,
-        message: String,
-    ) -> Result<(), frankenstein::Error> {
+    fn send_image_message(&self, image_url: String, message: String) -> Result<(), Box<dyn Error>> {
         // get file content from URL
-        let resp = get_client()
-            .expect("unable to build client")
-            .get(&*image_url)
-            .send()
-            .expect("request failed");
-        let body = resp.bytes().expect("body invalid");
+        let resp = self.client.get(&*image_url).send()?.error_for_status()?;
+        let body = resp.bytes()?;
 
         // create tmp dir
-        let dir = tempdir().expect("unable to create tmp dir");
+        let dir = tempdir()?;
         // retrieve file name from URL and create the file path in the tmp dir
-        let ancestors = Path::new(&*image_url).file_name().unwrap();
-        let file_path = dir.path().join(ancestors.to_str().unwrap());
+        let file_name = Path::new(&*image_url)
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .ok_or_else(|| format!("unable to retrieve file name from image URL {}", image_url))?;
+        let file_path = dir.path().join(file_name);
 
         // download file
-        std::fs::write(file_path.as_path(), &body).expect("unable to download file");
+        std::fs::write(file_path.as_path(), &body)?;
 
         let file = frankenstein::api_params::File::InputFile(InputFile { path: file_path });
         let params = SendPhotoParamsBuilder::default()
@@ -85,10 +81,12 @@ impl Telegram {
             .build()
             .unwrap();
 
-        self.api.send_photo(&params)?;
+        self.api
+            .send_photo(&params)
+            .map_err(|err| format!("unable to send photo (err: {:?})", err))?;
 
         // drop tmp dir again to delete all files in it
-        dir.close().expect("unable to close tmp dir");
+        dir.close()?;
 
         Ok(())
     }
@@ -101,12 +99,21 @@ impl Notification for Telegram {
              <b>{}</b>\n\
              <b>{}</b>\n\
              \n\
-             price: <b>{:.2} {}</b>\n\
-             price with taxes: <b>{:.2} {}</b>\n\
-             price with shipping and taxes: <b>{:.2} {}</b>\n\
-             raw price: <b>{:.2} {}</b>\n\
+             price: <b>{}</b>\n\
+             price with taxes: <b>{}</b>\n\
+             \n\
+             fees: <b>{}</b>\n\
+             shipping: <b>{}</b>\n\
+             customs duty: <b>{}</b>\n\
+             taxes ({:.1}%): <b>{}</b>\n\
+             customs clearance fee: <b>{}</b>\n\
+             total: <b>{}</b>\n\
+             \n\
+             raw price: <b>{}</b>\n\
              \n\
              item condition: <b>{:?}</b>\n\
+             availability: <b>{}</b>\n\
+             auction: <b>{}</b>\n\
              \n\
              notification type: <b>{:?}</b>\n\
              requested item condition: <b>{:?}</b>\n\
@@ -116,14 +123,18 @@ impl Notification for Telegram {
             item.description,
             item.jan,
             price.converted_price,
-            price.converted_currency,
             price.get_converted_taxed(),
-            price.converted_currency,
+            price.fees,
+            price.shipping,
+            price.duty,
+            price.taxes * 100.0,
+            price.get_converted_taxes(),
+            price.clearance_fee,
             price.get_converted_total(),
-            price.converted_currency,
             price.price,
-            price.currency,
             price.condition,
+            price.get_availability_text(),
+            price.get_auction_text(),
             cond.condition_type,
             cond.item_condition,
             cond.value,
@@ -131,9 +142,10 @@ impl Notification for Telegram {
         );
         if !item.image.is_empty() {
             self.send_image_message(item.image, msg)
-                .expect("unable to send image message");
+                .map_err(|err| format!("unable to send image message (err: {:?})", err))?;
         } else {
-            self.send_message(msg).expect("unable to send message");
+            self.send_message(msg)
+                .map_err(|err| format!("unable to send message (err: {:?})", err))?;
         }
 
         Ok(())
@@ -145,7 +157,7 @@ pub fn test_image_message() {
     let api_data = Some(TelegramApiData::new("token".to_string(), 0));
 
     if let Some(data) = api_data.as_ref() {
-        Telegram::new(data.clone())
+        Telegram::new(data.clone(), reqwest::blocking::Client::new())
             .send_image_message(
                 "https://static.myfigurecollection.net/pics/figure/large/740258.jpg".to_string(),
                 "<b>test</b> image".to_string(),
@@ -0,0 +1,188 @@
+use clap::ArgEnum;
+use serde_json::{Map, Value};
+
+/// Available output formats for the displayed database entries
+#[derive(ArgEnum, Clone, Copy, Debug)]
+pub(crate) enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+/// Table is a simple tabular representation of database entries
+/// which can be rendered into every supported output format
+pub(crate) struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    /// create a new empty table with the passed column headers
+    pub fn new(headers: Vec<&str>) -> Self {
+        Table {
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: vec![],
+        }
+    }
+
+    /// append a row to the table, the values have to be in the same order as the headers
+    pub fn add_row(&mut self, row: Vec<Value>) {
+        self.rows.push(row);
+    }
+
+    /// render the table in the requested output format
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Table => self.render_table(),
+            OutputFormat::Json => self.render_json(),
+            OutputFormat::Csv => self.render_csv(),
+        }
+    }
+
+    /// human readable representation of a single cell, floats are displayed with 2 decimals
+    fn cell_to_string(value: &Value) -> String {
+        match value {
+            Value::Null => "".to_string(),
+            Value::String(value) => value.clone(),
+            Value::Number(number) if number.is_f64() => {
+                format!("{:.2}", number.as_f64().unwrap())
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// render the rows as aligned columns with a separator line below the headers
+    fn render_table(&self) -> String {
+        let mut widths: Vec<usize> = self
+            .headers
+            .iter()
+            .map(|header| header.chars().count())
+            .collect();
+
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Table::cell_to_string).collect())
+            .collect();
+
+        for row in rows.iter() {
+            for (i, cell) in row.iter().enumerate() {
+                if cell.chars().count() > widths[i] {
+                    widths[i] = cell.chars().count();
+                }
+            }
+        }
+
+        let format_line = |cells: &[String]| -> String {
+            cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| format!("{:width$}", cell, width = widths[i]))
+                .collect::<Vec<String>>()
+                .join(" | ")
+                .trim_end()
+                .to_string()
+        };
+
+        let mut lines = vec![format_line(&self.headers)];
+        lines.push(
+            widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<String>>()
+                .join("-+-"),
+        );
+
+        for row in rows.iter() {
+            lines.push(format_line(row));
+        }
+
+        lines.join("\n")
+    }
+
+    /// render the rows as JSON array of objects with the headers as keys
+    fn render_json(&self) -> String {
+        let objects: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut object = Map::new();
+                for (i, value) in row.iter().enumerate() {
+                    object.insert(self.headers[i].clone(), value.clone());
+                }
+                Value::Object(object)
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&objects).unwrap()
+    }
+
+    /// render the rows as CSV (RFC 4180) including a header line
+    fn render_csv(&self) -> String {
+        let escape = |cell: String| -> String {
+            if cell.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell
+            }
+        };
+
+        let mut lines = vec![self
+            .headers
+            .iter()
+            .map(|header| escape(header.clone()))
+            .collect::<Vec<String>>()
+            .join(",")];
+
+        for row in self.rows.iter() {
+            lines.push(
+                row.iter()
+                    .map(|value| escape(Table::cell_to_string(value)))
+                    .collect::<Vec<String>>()
+                    .join(","),
+            );
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[test]
+fn test_render_table() {
+    let mut table = Table::new(vec!["id", "description", "price"]);
+    table.add_row(vec![
+        Value::from(1),
+        Value::from("Saber"),
+        Value::from(12.5),
+    ]);
+    table.add_row(vec![Value::from(12), Value::Null, Value::from(1500)]);
+
+    assert_eq!(
+        table.render(OutputFormat::Table),
+        "id | description | price\n\
+         ---+-------------+------\n\
+         1  | Saber       | 12.50\n\
+         12 |             | 1500"
+    );
+}
+
+#[test]
+fn test_render_csv() {
+    let mut table = Table::new(vec!["id", "description"]);
+    table.add_row(vec![Value::from(1), Value::from("Saber, 1/7 \"Alter\"")]);
+
+    assert_eq!(
+        table.render(OutputFormat::Csv),
+        "id,description\n1,\"Saber, 1/7 \"\"Alter\"\"\""
+    );
+}
+
+#[test]
+fn test_render_json() {
+    let mut table = Table::new(vec!["id", "description"]);
+    table.add_row(vec![Value::from(1), Value::from("Saber")]);
+
+    let rendered: Value = serde_json::from_str(&table.render(OutputFormat::Json)).unwrap();
+    assert_eq!(rendered[0]["id"], 1);
+    assert_eq!(rendered[0]["description"], "Saber");
+}